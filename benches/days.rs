@@ -0,0 +1,107 @@
+/*
+ * Benchmarks for every solution, on the real input if it exists and on a large generated one.
+ * Each day is included as a module with `include!`, so its benchmarks can reach the private
+ * parsing functions too. Days that parse as they go only have benchmarks for the parts.
+ */
+use advent_of_code::gen;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::fs;
+
+/// Inputs to run a day on: the real one, if it was downloaded, and a generated one of `size`.
+fn inputs(day: u8, size: usize) -> Vec<(String, String)> {
+    let mut inputs = vec![];
+    let real = fs::read_to_string(format!("src/inputs/{:02}.txt", day)).unwrap_or_default();
+    if !real.trim().is_empty() {
+        inputs.push(("input".to_string(), real));
+    }
+    if let Some(generated) = gen::generate(day, size, 1) {
+        inputs.push((format!("generated {}", size), generated));
+    }
+    inputs
+}
+
+fn bench_day<P, A, B>(
+    c: &mut Criterion,
+    day: u8,
+    size: usize,
+    parse: Option<fn(&str) -> P>,
+    part_one: fn(&str) -> Option<A>,
+    part_two: fn(&str) -> Option<B>,
+) {
+    let mut group = c.benchmark_group(format!("day{:02}", day));
+    for (name, input) in inputs(day, size) {
+        let input = input.as_str();
+        // a part that gives up early would only time how fast it fails.
+        assert!(
+            part_one(input).is_some() && part_two(input).is_some(),
+            "day {} has no answer for the {} input",
+            day,
+            name
+        );
+        if let Some(parse) = parse {
+            group.bench_with_input(BenchmarkId::new("parse", &name), input, |b, input| {
+                b.iter(|| parse(black_box(input)))
+            });
+        }
+        group.bench_with_input(BenchmarkId::new("part_one", &name), input, |b, input| {
+            b.iter(|| part_one(black_box(input)))
+        });
+        group.bench_with_input(BenchmarkId::new("part_two", &name), input, |b, input| {
+            b.iter(|| part_two(black_box(input)))
+        });
+    }
+    group.finish();
+}
+
+/// `day!(module, day, path, size)` includes the solution at `path` as `module` and benchmarks
+/// it on a generated input of `size`. `parse:` adds a benchmark of a parsing function and
+/// `part_two:` replaces the part two that is timed.
+macro_rules! day {
+    ($module:ident, $day:literal, $path:literal, $size:literal $(, parse: $parse:expr)? $(, part_two: $two:expr)?) => {
+        // the binaries' `main` and tests are not used here.
+        #[allow(unused)]
+        mod $module {
+            include!($path);
+
+            pub fn bench(c: &mut criterion::Criterion) {
+                let parse = day!(@parse $($parse)?);
+                let part_two = day!(@part_two $($two)?);
+                super::bench_day(c, $day, $size, parse, part_one, part_two);
+            }
+        }
+    };
+    (@parse) => { None::<fn(&str)> };
+    (@parse $parse:expr) => { Some($parse as fn(&str) -> _) };
+    (@part_two) => { part_two };
+    (@part_two $two:expr) => { $two as fn(&str) -> Option<_> };
+}
+
+day!(day01, 1, "../src/bin/01.rs", 10000, parse: parse);
+day!(day02, 2, "../src/bin/02.rs", 100000, parse: |input| {
+    let strategy = Strategy::hands(&["A", "B", "C"], &["X", "Y", "Z"]);
+    rounds(&Game::rock_paper_scissors(), &strategy, input)
+});
+day!(day03, 3, "../src/bin/03.rs", 30000);
+day!(day04, 4, "../src/bin/04.rs", 100000);
+day!(day05, 5, "../src/bin/05.rs", 10000, parse: parse_input);
+day!(day06, 6, "../src/bin/06.rs", 1000000);
+day!(day07, 7, "../src/bin/07.rs", 10000, parse: parse_input);
+day!(day08, 8, "../src/bin/08.rs", 500, parse: parse);
+day!(day09, 9, "../src/bin/09.rs", 10000, parse: parse::<2>);
+// part two prints the screen, so only render it.
+day!(day10, 10, "../src/bin/10.rs", 100000, parse: |input| Program::try_from(input), part_two: screen);
+
+criterion_group!(
+    benches,
+    day01::bench,
+    day02::bench,
+    day03::bench,
+    day04::bench,
+    day05::bench,
+    day06::bench,
+    day07::bench,
+    day08::bench,
+    day09::bench,
+    day10::bench
+);
+criterion_main!(benches);
@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/bin/09.rs"]
+mod day09;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        // the rope takes one step per unit of distance and remembers every position of the
+        // tail, so long walks are slow and hungry, not wrong. Skip them.
+        let total = input
+            .split_whitespace()
+            .filter_map(|word| word.parse::<u64>().ok())
+            .fold(0, u64::saturating_add);
+        if total > 100_000 {
+            return;
+        }
+        day09::part_one(input);
+        day09::part_two(input);
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+#[allow(dead_code)]
+#[path = "../../src/bin/10.rs"]
+mod day10;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        day10::part_one(input);
+        // `part_two` would print the screen on every run.
+        day10::screen(input);
+    }
+});
@@ -1,22 +1,162 @@
+use advent_of_code::helpers::{finish_args, top_k};
+use std::{
+    fmt::{self, Display},
+    process,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseError {
+    NotANumber {
+        line: usize,
+    },
+    /// The elf's calories so far, up to this line, do not fit in a `u32`.
+    TooMany {
+        line: usize,
+    },
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::NotANumber { line } => {
+                write!(f, "line {}: expected a number of calories", line)
+            }
+            ParseError::TooMany { line } => {
+                write!(
+                    f,
+                    "line {}: calories add up to more than {}",
+                    line,
+                    u32::MAX
+                )
+            }
+        }
+    }
+}
+
+/// Total calories carried by each elf. Elves are separated by blank lines, which may hold
+/// whitespace, and lines may end in CRLF.
+fn parse(input: &str) -> Result<Vec<u32>, ParseError> {
+    let mut totals = vec![];
+    let mut current = None;
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            totals.extend(current.take());
+            continue;
+        }
+        let calories = line
+            .parse::<u32>()
+            .map_err(|_| ParseError::NotANumber { line: i + 1 })?;
+        let total = current.unwrap_or(0u32).checked_add(calories);
+        current = Some(total.ok_or(ParseError::TooMany { line: i + 1 })?);
+    }
+    totals.extend(current);
+    Ok(totals)
+}
+
+/// Summary of how many calories the elves carry.
+struct Stats {
+    /// (elf number, total), most calories first.
+    ranks: Vec<(usize, u32)>,
+}
+
+impl Stats {
+    fn new(totals: &[u32]) -> Self {
+        let ranked = totals.iter().enumerate().map(|(i, &total)| (total, i + 1));
+        let ranks = top_k(ranked, totals.len());
+        Self {
+            ranks: ranks.into_iter().map(|(total, elf)| (elf, total)).collect(),
+        }
+    }
+
+    fn mean(&self) -> f64 {
+        let sum = self.ranks.iter().map(|&(_, t)| t as f64).sum::<f64>();
+        sum / self.ranks.len() as f64
+    }
+
+    fn median(&self) -> f64 {
+        let n = self.ranks.len();
+        let total = |i: usize| self.ranks[i].1 as f64;
+        match n % 2 {
+            0 => (total(n / 2 - 1) + total(n / 2)) / 2.0,
+            _ => total(n / 2),
+        }
+    }
+
+    /// Number of elves per bucket, for `buckets` equally wide buckets from the smallest
+    /// total to the largest, together with the lower bound of each bucket.
+    fn histogram(&self, buckets: usize) -> Vec<(u32, usize)> {
+        let (Some(&(_, max)), Some(&(_, min))) = (self.ranks.first(), self.ranks.last()) else {
+            return vec![];
+        };
+        let width = ((max - min) / buckets as u32).max(1);
+        let mut counts = vec![0; buckets];
+        for &(_, total) in &self.ranks {
+            counts[(((total - min) / width) as usize).min(buckets - 1)] += 1;
+        }
+        // with totals close to `u32::MAX` and a width of 1, upper buckets start past it.
+        let bounds = (0..).map(|b| min.saturating_add(b * width));
+        bounds.zip(counts).collect()
+    }
+}
+
+impl Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ranks.is_empty() {
+            return writeln!(f, "no elves");
+        }
+        for (rank, (elf, total)) in self.ranks.iter().enumerate() {
+            writeln!(f, "#{:<4} elf {:<5} {:>7}", rank + 1, elf, total)?;
+        }
+        writeln!(f, "mean {:.1}, median {:.1}", self.mean(), self.median())?;
+        let histogram = self.histogram(10);
+        let most = histogram.iter().map(|&(_, n)| n).max().unwrap_or(1);
+        for (from, n) in histogram {
+            writeln!(f, "{:>7} | {:<40} {}", from, "#".repeat(n * 40 / most), n)?;
+        }
+        Ok(())
+    }
+}
+
 pub fn part_one(input: &str) -> Option<u32> {
-    input
-        .split("\n\n")
-        .map(|v| v.lines().map(|v| v.parse::<u32>().unwrap()).sum())
-        .max()
+    top_k(parse(input).ok()?, 1).first().copied()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let mut sums: Vec<u32> = input
-        .split("\n\n")
-        .map(|v| v.lines().map(|v| v.parse::<u32>().unwrap()).sum())
-        .collect();
+    top_k(parse(input).ok()?, 3)
+        .into_iter()
+        .try_fold(0u32, |sum, total| sum.checked_add(total))
+}
+
+struct Args {
+    stats: bool,
+}
 
-    sums.sort_by(|a, b| b.cmp(a));
-    Some(sums.into_iter().take(3).sum())
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        stats: args.contains("--stats"),
+    };
+    finish_args(args)?;
+    Ok(parsed)
 }
 
 fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!("usage: `cargo solve 01 -- [--stats]`");
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 1);
+    if args.stats {
+        match parse(input) {
+            Ok(totals) => print!("{}", Stats::new(&totals)),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -36,4 +176,33 @@ mod tests {
         let input = advent_of_code::read_file("examples", 1);
         assert_eq!(part_two(&input), Some(45000));
     }
+
+    #[test]
+    fn test_parse() {
+        let input = advent_of_code::read_file("examples", 1);
+        let crlf = input.replace('\n', " \r\n") + "\r\n \r\n";
+        assert_eq!(parse(&crlf), parse(&input));
+        assert_eq!(parse(&input), Ok(vec![6000, 4000, 11000, 24000, 10000]));
+        assert_eq!(parse("1\n\nx"), Err(ParseError::NotANumber { line: 3 }));
+        assert_eq!(
+            parse("4294967295\n1").unwrap_err().to_string(),
+            "line 2: calories add up to more than 4294967295"
+        );
+        assert_eq!(part_one("4294967295\n\n1"), Some(u32::MAX));
+        assert_eq!(part_two("4294967295\n\n1"), None);
+    }
+
+    #[test]
+    fn test_stats() {
+        let input = advent_of_code::read_file("examples", 1);
+        let stats = Stats::new(&parse(&input).unwrap());
+        assert_eq!(stats.ranks[..2], [(4, 24000), (3, 11000)]);
+        assert_eq!(stats.mean(), 11000.0);
+        assert_eq!(stats.median(), 10000.0);
+        let histogram = stats.histogram(4);
+        assert_eq!(histogram, [(4000, 2), (9000, 2), (14000, 0), (19000, 1)]);
+
+        let stats = Stats::new(&[u32::MAX]);
+        assert_eq!(stats.histogram(2), [(u32::MAX, 1), (u32::MAX, 0)]);
+    }
 }
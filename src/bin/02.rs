@@ -1,125 +1,435 @@
-#[derive(Debug, Clone, Copy)]
-enum RPS {
-    Rock,
-    Paper,
-    Scissors,
-}
-
-impl RPS {
-    fn vs(&self, other: &RPS) -> RPSResult {
-        match (self, other) {
-            (RPS::Rock, RPS::Rock) | (RPS::Paper, RPS::Paper) | (RPS::Scissors, RPS::Scissors) => {
-                RPSResult::Draw
-            }
-            (RPS::Rock, RPS::Paper) | (RPS::Paper, RPS::Scissors) | (RPS::Scissors, RPS::Rock) => {
-                RPSResult::Lose
-            }
-            (RPS::Rock, RPS::Scissors) | (RPS::Scissors, RPS::Paper) | (RPS::Paper, RPS::Rock) => {
-                RPSResult::Win
-            }
+use advent_of_code::helpers::finish_args;
+use itertools::Itertools;
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+    process,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RPSResult {
+    Win,
+    Lose,
+    Draw,
+}
+
+/// A cyclic dominance game: hands are numbered `0..n` for an odd `n`, and hand `i` beats
+/// hand `j` when `(i - j) mod n` is odd, so every hand beats exactly half of the others.
+/// With Rock, Paper, Scissors in that order this is the classic game, and Rock, Paper,
+/// Scissors, Spock, Lizard gives the five hand variant.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Game {
+    names: Vec<String>,
+    /// Score for playing each hand.
+    hand_scores: Vec<u32>,
+    /// Score for losing, drawing and winning a round.
+    outcome_scores: [u32; 3],
+}
+
+impl Game {
+    fn new(names: &[&str]) -> Self {
+        assert!(
+            names.len() % 2 == 1,
+            "cyclic games need an odd number of hands"
+        );
+        Self {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            hand_scores: (1..=names.len() as u32).collect(),
+            outcome_scores: [0, 3, 6],
         }
     }
 
-    fn score(&self) -> u32 {
-        match self {
-            RPS::Rock => 1,
-            RPS::Paper => 2,
-            RPS::Scissors => 3,
+    fn rock_paper_scissors() -> Self {
+        Self::new(&["Rock", "Paper", "Scissors"])
+    }
+
+    fn rock_paper_scissors_spock_lizard() -> Self {
+        Self::new(&["Rock", "Paper", "Scissors", "Spock", "Lizard"])
+    }
+
+    fn with_hand_scores(mut self, scores: Vec<u32>) -> Self {
+        assert_eq!(scores.len(), self.hands(), "need a score for every hand");
+        self.hand_scores = scores;
+        self
+    }
+
+    fn with_outcome_scores(mut self, lose: u32, draw: u32, win: u32) -> Self {
+        self.outcome_scores = [lose, draw, win];
+        self
+    }
+
+    fn hands(&self) -> usize {
+        self.names.len()
+    }
+
+    fn outcome(&self, me: usize, them: usize) -> RPSResult {
+        let n = self.hands();
+        match (me + n - them) % n {
+            0 => RPSResult::Draw,
+            d if d % 2 == 1 => RPSResult::Win,
+            _ => RPSResult::Lose,
         }
     }
 
-    fn select_hand(&self, result: RPSResult) -> Self {
-        match (self, result) {
-            (RPS::Rock, RPSResult::Win) => RPS::Paper,
-            (RPS::Rock, RPSResult::Lose) => RPS::Scissors,
-            (RPS::Rock, RPSResult::Draw) => RPS::Rock,
-            (RPS::Paper, RPSResult::Win) => RPS::Scissors,
-            (RPS::Paper, RPSResult::Lose) => RPS::Rock,
-            (RPS::Paper, RPSResult::Draw) => RPS::Paper,
-            (RPS::Scissors, RPSResult::Win) => RPS::Rock,
-            (RPS::Scissors, RPSResult::Lose) => RPS::Paper,
-            (RPS::Scissors, RPSResult::Draw) => RPS::Scissors,
+    fn outcome_score(&self, outcome: RPSResult) -> u32 {
+        match outcome {
+            RPSResult::Lose => self.outcome_scores[0],
+            RPSResult::Draw => self.outcome_scores[1],
+            RPSResult::Win => self.outcome_scores[2],
         }
     }
+
+    /// My score for a round.
+    fn score(&self, me: usize, them: usize) -> u32 {
+        self.hand_scores[me] + self.outcome_score(self.outcome(me, them))
+    }
+
+    /// The hand that gets `outcome` against `them`. If several do, the one scoring most.
+    fn hand_for(&self, them: usize, outcome: RPSResult) -> usize {
+        (0..self.hands())
+            .filter(|&me| self.outcome(me, them) == outcome)
+            .max_by_key(|&me| self.hand_scores[me])
+            .expect("every outcome is possible against every hand")
+    }
+}
+
+/// What the second column of the strategy guide asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Response {
+    Hand(usize),
+    Outcome(RPSResult),
 }
 
-impl TryFrom<&str> for RPS {
-    type Error = ();
+/// How to read the strategy guide: which symbol stands for which opponent hand and which
+/// for what response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Strategy {
+    opponent: HashMap<String, usize>,
+    response: HashMap<String, Response>,
+}
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "A" | "X" => Ok(RPS::Rock),
-            "B" | "Y" => Ok(RPS::Paper),
-            "C" | "Z" => Ok(RPS::Scissors),
-            _ => Err(()),
+impl Strategy {
+    /// The `i`th opponent symbol is hand `i`, the `i`th response symbol is whatever the
+    /// `i`th response is.
+    fn new(opponent: &[&str], response: &[&str], responses: &[Response]) -> Self {
+        Self {
+            opponent: opponent.iter().map(|s| s.to_string()).zip(0..).collect(),
+            response: response
+                .iter()
+                .map(|s| s.to_string())
+                .zip(responses.iter().copied())
+                .collect(),
         }
     }
+
+    /// The response symbols are hands, in order.
+    fn hands(opponent: &[&str], response: &[&str]) -> Self {
+        let hands = (0..response.len()).map(Response::Hand).collect::<Vec<_>>();
+        Self::new(opponent, response, &hands)
+    }
+
+    /// The response symbols are lose, draw and win.
+    fn outcomes(opponent: &[&str], response: &[&str]) -> Self {
+        let outcomes = [RPSResult::Lose, RPSResult::Draw, RPSResult::Win].map(Response::Outcome);
+        Self::new(opponent, response, &outcomes)
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum RPSResult {
-    Win,
-    Lose,
-    Draw,
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Error {
+    line: usize,
+    kind: ErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ErrorKind {
+    MissingColumn,
+    UnknownSymbol(String),
 }
 
-impl RPSResult {
-    fn score(&self) -> u32 {
-        match self {
-            RPSResult::Win => 6,
-            RPSResult::Lose => 0,
-            RPSResult::Draw => 3,
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::MissingColumn => write!(f, "line {}: expected two symbols", self.line),
+            ErrorKind::UnknownSymbol(s) => write!(f, "line {}: unknown symbol {}", self.line, s),
         }
     }
 }
 
-impl TryFrom<&str> for RPSResult {
-    type Error = ();
+/// The rounds of the strategy guide as (my hand, their hand).
+fn rounds(game: &Game, strategy: &Strategy, input: &str) -> Result<Vec<(usize, usize)>, Error> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            let error = |kind| Error { line: i + 1, kind };
+            let unknown = |s: &str| error(ErrorKind::UnknownSymbol(s.to_string()));
+            let mut symbols = line.split_whitespace();
+            let (Some(them), Some(me)) = (symbols.next(), symbols.next()) else {
+                return Err(error(ErrorKind::MissingColumn));
+            };
+            let them = *strategy.opponent.get(them).ok_or_else(|| unknown(them))?;
+            let me = match strategy.response.get(me).ok_or_else(|| unknown(me))? {
+                Response::Hand(hand) => *hand,
+                Response::Outcome(outcome) => game.hand_for(them, *outcome),
+            };
+            Ok((me, them))
+        })
+        .collect()
+}
+
+fn play(game: &Game, strategy: &Strategy, input: &str) -> Result<u32, Error> {
+    let rounds = rounds(game, strategy, input)?;
+    Ok(rounds.iter().map(|&(me, them)| game.score(me, them)).sum())
+}
+
+/// Total score of the guide for every way of reading the response symbols as hands, best
+/// first.
+fn rank_mappings(
+    game: &Game,
+    opponent: &[&str],
+    response: &[&str],
+    input: &str,
+) -> Result<Vec<(Vec<usize>, u32)>, Error> {
+    let mut totals = (0..game.hands())
+        .permutations(response.len())
+        .map(|hands| {
+            let responses = hands.iter().copied().map(Response::Hand).collect_vec();
+            let total = play(game, &Strategy::new(opponent, response, &responses), input)?;
+            Ok((hands, total))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    totals.sort_by_key(|(_, total)| std::cmp::Reverse(*total));
+    Ok(totals)
+}
+
+/// Expected score of a round when I play hand `i` with probability `mine[i]` and the
+/// opponent plays hand `j` with probability `theirs[j]`.
+fn expected_score(game: &Game, mine: &[f64], theirs: &[f64]) -> f64 {
+    (0..game.hands())
+        .cartesian_product(0..game.hands())
+        .map(|(me, them)| mine[me] * theirs[them] * game.score(me, them) as f64)
+        .sum()
+}
+
+/// A symmetric equilibrium: a mix of hands that is a best response to itself when both
+/// players go for their own score. Found by support enumeration: for every set of hands,
+/// smallest first, solve for the mix over them that makes the opponent's hands in the set
+/// score the same, and accept it if no hand outside the set scores more.
+fn equilibrium(game: &Game) -> Option<Vec<f64>> {
+    const EPS: f64 = 1e-9;
+    let n = game.hands();
+    let payoff = |me, them| game.score(me, them) as f64;
+    (1..=n)
+        .flat_map(|size| (0..n).combinations(size))
+        .find_map(|support| {
+            let k = support.len();
+            // unknowns: the probabilities of the hands in the support, then the value v.
+            let mut system = support
+                .iter()
+                .map(|&me| {
+                    let mut row = support.iter().map(|&them| payoff(me, them)).collect_vec();
+                    row.extend([-1.0, 0.0]);
+                    row
+                })
+                .collect_vec();
+            let mut total = vec![1.0; k + 2];
+            total[k] = 0.0;
+            system.push(total);
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match value {
-            "Z" => Ok(RPSResult::Win),
-            "X" => Ok(RPSResult::Lose),
-            "Y" => Ok(RPSResult::Draw),
-            _ => Err(()),
+            let solution = gaussian_elimination(system)?;
+            let (probabilities, value) = (&solution[..k], solution[k]);
+            if probabilities.iter().any(|&p| p < -EPS) {
+                return None;
+            }
+            let mut mix = vec![0.0; n];
+            for (&hand, &p) in support.iter().zip(probabilities) {
+                mix[hand] = p.max(0.0);
+            }
+            let score = |me| (0..n).map(|them| mix[them] * payoff(me, them)).sum::<f64>();
+            (0..n).all(|me| score(me) <= value + EPS).then_some(mix)
+        })
+}
+
+/// Solves the linear system given as augmented rows, or `None` if it is singular.
+fn gaussian_elimination(mut rows: Vec<Vec<f64>>) -> Option<Vec<f64>> {
+    let n = rows.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&a, &b| rows[a][col].abs().total_cmp(&rows[b][col].abs()))?;
+        if rows[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        rows.swap(col, pivot);
+        let pivot = rows[col].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            if r == col {
+                continue;
+            }
+            let factor = row[col] / pivot[col];
+            for (x, p) in row.iter_mut().zip(&pivot).skip(col) {
+                *x -= factor * p;
+            }
         }
     }
+    Some((0..n).map(|i| rows[i][n] / rows[i][i]).collect())
+}
+
+fn analyze(game: &Game, opponent: &[&str], response: &[&str], input: &str) -> Result<(), Error> {
+    let name = |hand: usize| game.names[hand].as_str();
+    let describe = |hands: &[usize]| {
+        (response.iter().zip(hands))
+            .map(|(symbol, &hand)| format!("{}={}", symbol, name(hand)))
+            .join(" ")
+    };
+    let totals = rank_mappings(game, opponent, response, input)?;
+    for (hands, total) in &totals {
+        println!("{:<40} {}", describe(hands), total);
+    }
+    if let (Some((best, high)), Some((worst, low))) = (totals.first(), totals.last()) {
+        println!("best:  {} scores {}", describe(best), high);
+        println!("worst: {} scores {}", describe(worst), low);
+    }
+
+    // how often the opponent plays each hand in the guide.
+    let strategy = Strategy::hands(opponent, response);
+    let rounds = rounds(game, &strategy, input)?;
+    let mut theirs = vec![0.0; game.hands()];
+    for (_, them) in &rounds {
+        theirs[*them] += 1.0 / rounds.len() as f64;
+    }
+    if let Some(mix) = equilibrium(game) {
+        let describe = mix
+            .iter()
+            .enumerate()
+            .map(|(h, p)| format!("{} {:.3}", name(h), p));
+        println!("equilibrium: {}", describe.collect_vec().join(", "));
+        println!(
+            "  expects {:.3} per round against itself, {:.3} against the guide's opponent",
+            expected_score(game, &mix, &mix),
+            expected_score(game, &mix, &theirs)
+        );
+    }
+    Ok(())
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
-    Some(
-        input
-            .lines()
-            .map(|line| {
-                let mut v = line.split_whitespace().flat_map(RPS::try_from);
-                let lhs = v.next().unwrap();
-                let rhs = v.next().unwrap();
-                rhs.vs(&lhs).score() + rhs.score()
-            })
-            .sum(),
-    )
+    let strategy = Strategy::hands(&["A", "B", "C"], &["X", "Y", "Z"]);
+    play(&Game::rock_paper_scissors(), &strategy, input).ok()
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    Some(
-        input
-            .lines()
-            .map(|line| {
-                let mut v = line.split_whitespace();
-                let lhs = v.next().unwrap();
-                let lhs = RPS::try_from(lhs).unwrap();
-
-                let rhs = v.next().unwrap();
-                let rhs = RPSResult::try_from(rhs).unwrap();
-                let rhs = lhs.select_hand(rhs);
-                rhs.vs(&lhs).score() + rhs.score()
-            })
-            .sum(),
-    )
+    let strategy = Strategy::outcomes(&["A", "B", "C"], &["X", "Y", "Z"]);
+    play(&Game::rock_paper_scissors(), &strategy, input).ok()
+}
+
+fn parse_scores(s: &str) -> Result<Vec<u32>, std::num::ParseIntError> {
+    s.split(',').map(str::parse).collect()
+}
+
+fn parse_mix(s: &str) -> Result<Vec<f64>, String> {
+    let mix = s
+        .split(',')
+        .map(str::parse::<f64>)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    if mix.iter().any(|&p| p < 0.0) || (mix.iter().sum::<f64>() - 1.0).abs() > 1e-6 {
+        return Err(format!("{} is not a probability distribution", s));
+    }
+    Ok(mix)
+}
+
+struct Args {
+    spock: bool,
+    analysis: bool,
+    mix: Option<Vec<f64>>,
+    hand_scores: Option<Vec<u32>>,
+    outcome_scores: Option<Vec<u32>>,
+}
+
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        spock: args.contains("--rpsls"),
+        analysis: args.contains("--analyze"),
+        mix: args.opt_value_from_fn("--mix", parse_mix)?,
+        hand_scores: args.opt_value_from_fn("--hand-scores", parse_scores)?,
+        outcome_scores: args.opt_value_from_fn("--outcome-scores", parse_scores)?,
+    };
+    finish_args(args)?;
+    Ok(parsed)
 }
 
 fn main() {
+    let Args {
+        spock,
+        analysis,
+        mix,
+        hand_scores,
+        outcome_scores,
+    } = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!(
+                "usage: `cargo solve 02 -- [--rpsls] [--analyze] [--mix p,...] \
+                 [--hand-scores n,...] [--outcome-scores lose,draw,win]`"
+            );
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 2);
+    if spock || analysis || mix.is_some() || hand_scores.is_some() || outcome_scores.is_some() {
+        let (mut game, opponent, response) = if spock {
+            (
+                Game::rock_paper_scissors_spock_lizard(),
+                ["A", "B", "C", "D", "E"].as_slice(),
+                ["V", "W", "X", "Y", "Z"].as_slice(),
+            )
+        } else {
+            (
+                Game::rock_paper_scissors(),
+                ["A", "B", "C"].as_slice(),
+                ["X", "Y", "Z"].as_slice(),
+            )
+        };
+        match hand_scores {
+            Some(scores) if scores.len() != game.hands() => {
+                return eprintln!("need a score for each of the {} hands", game.hands());
+            }
+            Some(scores) => game = game.with_hand_scores(scores),
+            None => {}
+        }
+        match outcome_scores.as_deref() {
+            Some(&[lose, draw, win]) => game = game.with_outcome_scores(lose, draw, win),
+            Some(_) => return eprintln!("need three outcome scores: lose, draw and win"),
+            None => {}
+        }
+        match play(&game, &Strategy::hands(opponent, response), input) {
+            Ok(score) => println!("{}: {}", game.names.join("-"), score),
+            Err(e) => return eprintln!("{}", e),
+        }
+        match mix {
+            Some(mix) if mix.len() != game.hands() => {
+                eprintln!("need a probability for each of the {} hands", game.hands());
+            }
+            Some(mix) => {
+                let uniform = vec![1.0 / game.hands() as f64; game.hands()];
+                let expected = expected_score(&game, &mix, &uniform);
+                println!(
+                    "mix expects {:.3} per round against a uniform opponent",
+                    expected
+                );
+            }
+            None => {}
+        }
+        if analysis {
+            if let Err(e) = analyze(&game, opponent, response, input) {
+                eprintln!("{}", e);
+            }
+        }
+        return;
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -139,4 +449,67 @@ mod tests {
         let input = advent_of_code::read_file("examples", 2);
         assert_eq!(part_two(&input), Some(12));
     }
+
+    #[test]
+    fn test_rules() {
+        let game = Game::rock_paper_scissors_spock_lizard();
+        let beats = |a, b| {
+            let hand = |name| game.names.iter().position(|n| n == name).unwrap();
+            game.outcome(hand(a), hand(b)) == RPSResult::Win
+        };
+        assert!(beats("Scissors", "Paper"));
+        assert!(beats("Paper", "Spock"));
+        assert!(beats("Rock", "Lizard"));
+        assert!(beats("Lizard", "Spock"));
+        assert!(beats("Spock", "Scissors"));
+        assert!(!beats("Spock", "Lizard"));
+        assert_eq!(game.hand_for(0, RPSResult::Win), 3);
+
+        let game = Game::rock_paper_scissors()
+            .with_hand_scores(vec![0, 0, 0])
+            .with_outcome_scores(0, 1, 2);
+        let strategy = Strategy::hands(&["A", "B", "C"], &["X", "Y", "Z"]);
+        let input = advent_of_code::read_file("examples", 2);
+        assert_eq!(play(&game, &strategy, &input), Ok(3));
+        assert_eq!(
+            play(&game, &strategy, "A X\nB W").unwrap_err().to_string(),
+            "line 2: unknown symbol W"
+        );
+        assert_eq!(
+            play(&game, &strategy, "A").unwrap_err().to_string(),
+            "line 1: expected two symbols"
+        );
+    }
+
+    #[test]
+    fn test_analysis() {
+        let game = Game::rock_paper_scissors();
+        let input = advent_of_code::read_file("examples", 2);
+        let totals = rank_mappings(&game, &["A", "B", "C"], &["X", "Y", "Z"], &input).unwrap();
+        assert_eq!(totals.len(), 6);
+        assert!(totals.contains(&(vec![0, 1, 2], 15)));
+        assert_eq!(totals[0], (vec![2, 1, 0], 24));
+        assert_eq!(totals[5].1, 6);
+
+        let uniform = [1.0 / 3.0; 3];
+        assert!((expected_score(&game, &uniform, &uniform) - 5.0).abs() < 1e-9);
+        assert_eq!(
+            expected_score(&game, &[0.0, 1.0, 0.0], &[1.0, 0.0, 0.0]),
+            8.0
+        );
+
+        // without hand scores the game is fair, so only the uniform mix is stable.
+        let fair = Game::rock_paper_scissors_spock_lizard().with_hand_scores(vec![0; 5]);
+        let mix = equilibrium(&fair).unwrap();
+        assert!(mix.iter().all(|p| (p - 0.2).abs() < 1e-9));
+
+        // otherwise no hand does better than the others against the equilibrium mix.
+        let mix = equilibrium(&game).unwrap();
+        assert!((mix.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        let value = expected_score(&game, &mix, &mix);
+        for hand in 0..3 {
+            let pure = [0, 1, 2].map(|h| if h == hand { 1.0 } else { 0.0 });
+            assert!(expected_score(&game, &pure, &mix) <= value + 1e-9);
+        }
+    }
 }
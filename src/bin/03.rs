@@ -1,54 +1,157 @@
-use std::collections::HashSet;
-
-fn score(ipt: char) -> u32 {
-    if ipt.is_ascii_lowercase() {
-        (ipt as u32) - b'a' as u32 + 1
-    } else if ipt.is_ascii_uppercase() {
-        (ipt as u32) - b'A' as u32 + 27
-    } else {
-        panic!("Invalid character")
+use advent_of_code::helpers::finish_args;
+use std::{
+    fmt::{self, Display},
+    ops::{BitAnd, BitOr},
+    process,
+};
+
+/// Set of item types as a bitset, with bit `p - 1` standing for the item of priority `p`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+struct ItemSet(u64);
+
+impl ItemSet {
+    const ALL: ItemSet = ItemSet((1 << 52) - 1);
+
+    /// Items `a` to `z` have priorities 1 to 26, `A` to `Z` 27 to 52.
+    fn priority(item: char) -> Option<u32> {
+        match item {
+            'a'..='z' => Some(item as u32 - 'a' as u32 + 1),
+            'A'..='Z' => Some(item as u32 - 'A' as u32 + 27),
+            _ => None,
+        }
+    }
+
+    /// The set of items in `items`, or the first character that is not an item.
+    fn parse(items: &str) -> Result<Self, char> {
+        items.chars().try_fold(Self::default(), |set, item| {
+            let priority = Self::priority(item).ok_or(item)?;
+            Ok(set | Self(1 << (priority - 1)))
+        })
+    }
+
+    fn priorities(self) -> impl Iterator<Item = u32> {
+        (1..=52).filter(move |p| self.0 & (1 << (p - 1)) != 0)
     }
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    Some(
-        input
-            .lines()
-            .map(|v| {
-                let (lhs, rhs) = v.split_at(v.len() / 2);
-                let [lhs, rhs] = [lhs, rhs].map(|v| v.chars().collect::<HashSet<_>>());
-                let chars = lhs.intersection(&rhs).copied().collect::<HashSet<_>>();
-                chars.into_iter().map(score).sum::<u32>()
-            })
-            .sum(),
-    )
+impl BitAnd for ItemSet {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut lines = input.lines().peekable();
-    let mut result = 0;
-    loop {
-        if lines.peek().is_none() {
-            break;
+impl BitOr for ItemSet {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    InvalidItem { line: usize, item: char },
+    IncompleteGroup { lines: usize, size: usize },
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidItem { line, item } => {
+                write!(f, "line {}: {:?} is not an item", line, item)
+            }
+            Error::IncompleteGroup { lines, size } => {
+                write!(
+                    f,
+                    "{} rucksacks do not split into groups of {}",
+                    lines, size
+                )
+            }
         }
-        let l1 = lines.next().unwrap();
-        let l2 = lines.next().unwrap();
-        let l3 = lines.next().unwrap();
-        let [l1, l2, l3] = [l1, l2, l3].map(|v| v.chars().collect::<HashSet<_>>());
-        let l = l1
-            .intersection(&l2)
-            .copied()
-            .collect::<HashSet<_>>()
-            .intersection(&l3)
-            .copied()
-            .collect::<HashSet<_>>();
-        result += l.into_iter().map(score).sum::<u32>();
     }
-    Some(result)
+}
+
+fn parse_line(line: usize, items: &str) -> Result<ItemSet, Error> {
+    ItemSet::parse(items).map_err(|item| Error::InvalidItem { line, item })
+}
+
+/// Sum of the priorities of the items found in both compartments of every rucksack.
+fn misplaced(input: &str) -> Result<u32, Error> {
+    input.lines().enumerate().try_fold(0, |sum, (i, line)| {
+        // items are ASCII, so once the line is checked, halving its bytes splits between items.
+        parse_line(i + 1, line)?;
+        let (lhs, rhs) = line.split_at(line.len() / 2);
+        let common = parse_line(i + 1, lhs)? & parse_line(i + 1, rhs)?;
+        Ok(sum + common.priorities().sum::<u32>())
+    })
+}
+
+/// Sum of the priorities of the items carried by every elf of a group, for groups of
+/// `size` consecutive rucksacks.
+fn badges(input: &str, size: usize) -> Result<u32, Error> {
+    assert!(size > 0, "groups must not be empty");
+    let lines = input.lines().collect::<Vec<_>>();
+    if lines.len() % size != 0 {
+        return Err(Error::IncompleteGroup {
+            lines: lines.len(),
+            size,
+        });
+    }
+    lines
+        .chunks(size)
+        .enumerate()
+        .try_fold(0, |sum, (g, group)| {
+            let common = group
+                .iter()
+                .enumerate()
+                .try_fold(ItemSet::ALL, |set, (i, line)| {
+                    Ok(set & parse_line(g * size + i + 1, line)?)
+                })?;
+            Ok(sum + common.priorities().sum::<u32>())
+        })
+}
+
+pub fn part_one(input: &str) -> Option<u32> {
+    misplaced(input).ok()
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    badges(input, 3).ok()
+}
+
+struct Args {
+    group: Option<usize>,
+}
+
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        group: args.opt_value_from_str("--group")?,
+    };
+    finish_args(args)?;
+    Ok(parsed)
 }
 
 fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!("usage: `cargo solve 03 -- [--group N]`");
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 3);
+    match args.group {
+        Some(0) => eprintln!("groups must not be empty"),
+        Some(size) => match badges(input, size) {
+            Ok(sum) => println!("badges of groups of {}: {}", size, sum),
+            Err(e) => eprintln!("{}", e),
+        },
+        None => {}
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -68,4 +171,39 @@ mod tests {
         let input = advent_of_code::read_file("examples", 3);
         assert_eq!(part_two(&input), Some(70));
     }
+
+    #[test]
+    fn test_item_set() {
+        let set = ItemSet::parse("aZzb").unwrap();
+        assert_eq!(set.priorities().collect::<Vec<_>>(), [1, 2, 26, 52]);
+        assert_eq!(
+            (set & ItemSet::parse("bZ").unwrap())
+                .priorities()
+                .sum::<u32>(),
+            54
+        );
+        assert_eq!(set | ItemSet::ALL, ItemSet::ALL);
+        assert_eq!(ItemSet::parse("ab1"), Err('1'));
+    }
+
+    #[test]
+    fn test_groups() {
+        let input = advent_of_code::read_file("examples", 3);
+        assert_eq!(badges(&input, 1), Ok(2278));
+        assert_eq!(badges(&input, 6), Ok(0));
+        assert_eq!(
+            badges(&input, 4),
+            Err(Error::IncompleteGroup { lines: 6, size: 4 })
+        );
+        assert_eq!(
+            misplaced("abca\nab-b").unwrap_err().to_string(),
+            "line 2: '-' is not an item"
+        );
+        assert_eq!(
+            misplaced("é"),
+            Err(Error::InvalidItem {
+                line: 1, item: 'é'
+            })
+        );
+    }
 }
@@ -20,23 +20,31 @@ where
     }
 }
 
+type Pair = (RangeInclusive<u32>, RangeInclusive<u32>);
+
+/// The pairs of section assignments, one `a-b,c-d` per line, or `None` if a line is not.
+fn parse(input: &str) -> Option<Vec<Pair>> {
+    input
+        .lines()
+        .map(|s| {
+            s.split(',')
+                .map(|v| {
+                    let (l, r) = v
+                        .split('-')
+                        .map(|v| v.parse::<u32>().ok())
+                        .collect_tuple::<(_, _)>()?;
+                    Some(l?..=r?)
+                })
+                .collect_tuple::<(_, _)>()
+                .and_then(|(l, r)| Some((l?, r?)))
+        })
+        .collect()
+}
+
 pub fn part_one(input: &str) -> Option<u32> {
     Some(
-        input
-            .lines()
-            .map(|s| {
-                s.split(',')
-                    .map(|v| {
-                        let (l, r) = v
-                            .split('-')
-                            .map(|v| v.parse::<u32>().unwrap())
-                            .collect_tuple::<(_, _)>()
-                            .expect("has 2 items");
-                        l..=r
-                    })
-                    .collect_tuple::<(_, _)>()
-                    .expect("has 2 items")
-            })
+        parse(input)?
+            .iter()
             .filter(|(l, r)| l.contains_range(r) || r.contains_range(l))
             .count() as u32,
     )
@@ -44,21 +52,8 @@ pub fn part_one(input: &str) -> Option<u32> {
 
 pub fn part_two(input: &str) -> Option<u32> {
     Some(
-        input
-            .lines()
-            .map(|s| {
-                s.split(',')
-                    .map(|v| {
-                        let (l, r) = v
-                            .split('-')
-                            .map(|v| v.parse::<u32>().unwrap())
-                            .collect_tuple::<(_, _)>()
-                            .expect("has 2 items");
-                        l..=r
-                    })
-                    .collect_tuple::<(_, _)>()
-                    .expect("has 2 items")
-            })
+        parse(input)?
+            .iter()
             .filter(|(l, r)| l.is_overlap(r))
             .count() as u32,
     )
@@ -85,4 +80,19 @@ mod tests {
         let input = advent_of_code::read_file("examples", 4);
         assert_eq!(part_two(&input), Some(4));
     }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("2-4,6-8\n"), Some(vec![(2..=4, 6..=8)]));
+        for input in [
+            "2-4",
+            "2-4,6",
+            "2-4,6-8,1-2",
+            "2-x,6-8",
+            "2-4-5,6-8",
+            "-4,6-8",
+        ] {
+            assert_eq!(parse(input), None, "{:?}", input);
+        }
+    }
 }
@@ -1,15 +1,24 @@
+use advent_of_code::helpers::finish_args;
 use itertools::Itertools;
+use std::{
+    collections::HashSet,
+    fmt::{self, Display},
+    hash::Hash,
+    io, process,
+    str::FromStr,
+};
+
 use nom::{
-    branch::{alt, permutation},
+    branch::alt,
     bytes::complete::tag,
-    character::complete::{anychar, digit1, newline},
-    combinator::{map, map_res},
-    multi::{many0, separated_list0, separated_list1},
-    sequence::delimited,
+    character::complete::{anychar, digit1},
+    combinator::{all_consuming, map, map_res},
+    multi::separated_list0,
+    sequence::{delimited, tuple},
     IResult,
 };
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 struct Crate(char);
 
 impl Crate {
@@ -30,29 +39,57 @@ fn parse_crate_or_space(s: &str) -> IResult<&str, Option<Crate>> {
     alt((map(parse_crate, Some), map(parse_space, |_| None)))(s)
 }
 
+/// A row of the drawing. Trailing whitespace is optional, so a row may have fewer cells
+/// than there are stacks.
 fn parse_crate_line(s: &str) -> IResult<&str, Vec<Option<Crate>>> {
-    separated_list1(tag(" "), parse_crate_or_space)(s)
+    all_consuming(separated_list0(tag(" "), parse_crate_or_space))(s.trim_end())
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 struct Stacks(Vec<Vec<Crate>>);
 
 impl Stacks {
-    fn process1(&mut self, op: Op) {
-        let from_idx = op.from - 1;
-        let to_idx = op.to - 1;
-        for _ in 0..op.num {
-            let a = self.0[from_idx].pop().expect("pop");
-            self.0[to_idx].push(a);
+    /// Checks that `op` names existing stacks and that there are enough crates to move.
+    fn check(&self, op: Op) -> Result<(), Error> {
+        let error = |kind| Error {
+            line: op.line,
+            kind,
+        };
+        for stack in [op.from, op.to] {
+            if stack == 0 || stack > self.0.len() {
+                return Err(error(ErrorKind::NoSuchStack(op, stack)));
+            }
+        }
+        let has = self.0[op.from - 1].len();
+        if has < op.num {
+            return Err(error(ErrorKind::NotEnoughCrates(op, has)));
+        }
+        Ok(())
+    }
+
+    /// Moves the top `num` crates from stack `from` to stack `to` (both 1-based), keeping
+    /// their order unless `reversed`. Moving crates onto their own stack leaves it as it was.
+    fn lift(&mut self, num: usize, from: usize, to: usize, reversed: bool) {
+        if from == to {
+            return;
+        }
+        let from = &mut self.0[from - 1];
+        let mut batch = from.split_off(from.len() - num);
+        if reversed {
+            batch.reverse();
         }
+        self.0[to - 1].append(&mut batch);
     }
 
-    fn process2(&mut self, op: Op) {
-        let from_idx = op.from - 1;
-        let to_idx = op.to - 1;
-        let len = self.0[from_idx].len() - op.num;
-        let mut v2 = self.0[from_idx].split_off(len);
-        self.0[to_idx].append(&mut v2);
+    /// Every legal op on the current stacks.
+    fn moves(&self) -> impl Iterator<Item = Op> + '_ {
+        let count = self.0.len();
+        (1..=count)
+            .cartesian_product(1..=count)
+            .filter(|(from, to)| from != to)
+            .flat_map(|(from, to)| {
+                (1..=self.0[from - 1].len()).map(move |num| Op::new(num, from, to))
+            })
     }
 
     fn tops(&self) -> String {
@@ -65,46 +102,91 @@ impl Stacks {
     }
 }
 
-impl From<Vec<Vec<Crate>>> for Stacks {
-    fn from(v: Vec<Vec<Crate>>) -> Self {
-        Self(v)
+/// Draws the stacks the way the puzzle input does, followed by the numbered label row.
+impl Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or(0);
+        for row in (0..height).rev() {
+            let line = self
+                .0
+                .iter()
+                .map(|stack| match stack.get(row) {
+                    Some(c) => format!("[{}]", c.0),
+                    None => "   ".to_string(),
+                })
+                .join(" ");
+            writeln!(f, "{}", line)?;
+        }
+        let labels = (1..=self.0.len()).map(|i| format!("{:^3}", i)).join(" ");
+        writeln!(f, "{}", labels)
     }
 }
 
-fn parse_crate_lines(s: &str) -> IResult<&str, Stacks> {
-    map(many0(permutation((parse_crate_line, newline))), |v| {
-        let stack_num = v[0].0.len();
-        v.into_iter()
-            .rev()
-            .fold(vec![vec![]; stack_num], |mut acc, w| {
-                let w = w.0;
-                for (i, crate_) in w.into_iter().enumerate() {
-                    if let Some(c) = crate_ {
-                        acc[i].push(c);
-                    }
+/// Builds the stacks from the drawing rows (top row first) and the label row.
+fn parse_drawing(rows: &[&str], labels: &str) -> Result<Stacks, Error> {
+    let labels_line = rows.len() + 1;
+    let count = labels.split_whitespace().count();
+    if count == 0
+        || !labels
+            .split_whitespace()
+            .map(str::parse::<usize>)
+            .eq((1..=count).map(Ok))
+    {
+        return Err(Error {
+            line: labels_line,
+            kind: ErrorKind::BadLabels,
+        });
+    }
+
+    let mut stacks = vec![vec![]; count];
+    for (i, row) in rows.iter().enumerate().rev() {
+        let error = |kind| Error { line: i + 1, kind };
+        let (_, cells) = parse_crate_line(row).map_err(|_| error(ErrorKind::BadDrawing))?;
+        if cells.len() > count {
+            return Err(error(ErrorKind::UnlabeledStack(count + 1)));
+        }
+        for (n, cell) in cells.into_iter().enumerate() {
+            if let Some(c) = cell {
+                // every row below this one must have had a crate in this stack.
+                if stacks[n].len() != rows.len() - 1 - i {
+                    return Err(error(ErrorKind::FloatingCrate(n + 1)));
                 }
-                acc
-            })
-            .into()
-    })(s)
+                stacks[n].push(c);
+            }
+        }
+    }
+    Ok(Stacks(stacks))
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 struct Op {
     num: usize,
     from: usize,
     to: usize,
+    /// Line of the input the op was read from, for error messages.
+    line: usize,
 }
 
 impl Op {
     pub fn new(num: usize, from: usize, to: usize) -> Self {
-        Self { num, from, to }
+        Self {
+            num,
+            from,
+            to,
+            line: 0,
+        }
+    }
+}
+
+impl Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.num, self.from, self.to)
     }
 }
 
 fn parse_procedure(s: &str) -> IResult<&str, Op> {
     map(
-        permutation((
+        tuple((
             tag("move "),
             map_res(digit1, str::parse),
             tag(" from "),
@@ -116,42 +198,445 @@ fn parse_procedure(s: &str) -> IResult<&str, Op> {
     )(s)
 }
 
-fn parse_procedures(s: &str) -> IResult<&str, Vec<Op>> {
-    separated_list0(newline, parse_procedure)(s)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Error {
+    line: usize,
+    kind: ErrorKind,
 }
 
-pub fn part_one(input: &str) -> Option<String> {
-    let (crates, procedure) = input
-        .split("\n\n")
-        .collect_tuple()
-        .expect("have crate and procedure");
-    let (_, mut stacks) = parse_crate_lines(crates).expect("parse stack error");
-    let (_, procedures) = parse_procedures(procedure).expect("parse procedure error");
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ErrorKind {
+    BadDrawing,
+    BadLabels,
+    UnlabeledStack(usize),
+    FloatingCrate(usize),
+    MissingProcedure,
+    BadMove,
+    NoSuchStack(Op, usize),
+    NotEnoughCrates(Op, usize),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            ErrorKind::BadDrawing => write!(f, "expected a row of crates like `[A]     [B]`"),
+            ErrorKind::BadLabels => write!(f, "expected stack labels counting up from 1"),
+            ErrorKind::UnlabeledStack(n) => write!(f, "crate in unlabeled stack {}", n),
+            ErrorKind::FloatingCrate(n) => {
+                write!(f, "crate floating above an empty spot in stack {}", n)
+            }
+            ErrorKind::MissingProcedure => {
+                write!(f, "expected a blank line followed by the procedure")
+            }
+            ErrorKind::BadMove => write!(f, "expected `move N from A to B`"),
+            ErrorKind::NoSuchStack(op, n) => write!(f, "{}: there is no stack {}", op, n),
+            ErrorKind::NotEnoughCrates(op, has) => {
+                write!(f, "{}: stack {} only has {} crates", op, op.from, has)
+            }
+        }
+    }
+}
+
+fn parse_input(input: &str) -> Result<(Stacks, Vec<Op>), Error> {
+    let lines = input.lines().collect_vec();
+    let blank = lines
+        .iter()
+        .position(|line| line.trim().is_empty())
+        .filter(|&blank| blank > 0)
+        .ok_or(Error {
+            line: lines.len().max(1),
+            kind: ErrorKind::MissingProcedure,
+        })?;
+    let stacks = parse_drawing(&lines[..blank - 1], lines[blank - 1])?;
+
+    let ops = lines
+        .iter()
+        .enumerate()
+        .skip(blank + 1)
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(
+            |(i, line)| match all_consuming(parse_procedure)(line.trim()) {
+                Ok((_, op)) => Ok(Op { line: i + 1, ..op }),
+                Err(_) => Err(Error {
+                    line: i + 1,
+                    kind: ErrorKind::BadMove,
+                }),
+            },
+        )
+        .collect::<Result<_, _>>()?;
+    Ok((stacks, ops))
+}
+
+/// A crane model deciding how the crates of a single `Op` are carried.
+trait CrateMover {
+    /// Carries out an op that passed `Stacks::check`.
+    fn carry(&mut self, stacks: &mut Stacks, op: Op);
+
+    fn apply(&mut self, stacks: &mut Stacks, op: Op) -> Result<(), Error> {
+        stacks.check(op)?;
+        self.carry(stacks, op);
+        Ok(())
+    }
+}
 
+/// Moves one crate at a time, so a batch ends up reversed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CrateMover9000;
+
+impl CrateMover for CrateMover9000 {
+    fn carry(&mut self, stacks: &mut Stacks, op: Op) {
+        stacks.lift(op.num, op.from, op.to, true);
+    }
+}
+
+/// Moves the whole batch at once, keeping its order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct CrateMover9001;
+
+impl CrateMover for CrateMover9001 {
+    fn carry(&mut self, stacks: &mut Stacks, op: Op) {
+        stacks.lift(op.num, op.from, op.to, false);
+    }
+}
+
+/// Lifts at most `capacity` crates at once, splitting larger moves into chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Capped {
+    capacity: usize,
+}
+
+impl CrateMover for Capped {
+    fn carry(&mut self, stacks: &mut Stacks, op: Op) {
+        let mut left = op.num;
+        while left > 0 {
+            let chunk = left.min(self.capacity);
+            stacks.lift(chunk, op.from, op.to, false);
+            left -= chunk;
+        }
+    }
+}
+
+/// Moves whole batches but reverses every other one, starting with the second.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+struct Alternating {
+    reverse: bool,
+}
+
+impl CrateMover for Alternating {
+    fn carry(&mut self, stacks: &mut Stacks, op: Op) {
+        stacks.lift(op.num, op.from, op.to, self.reverse);
+        self.reverse = !self.reverse;
+    }
+}
+
+/// The built-in crane models, by name: `9000`, `9001`, `capped:N` or `alternating`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Crane {
+    Mover9000,
+    Mover9001,
+    Capped(Capped),
+    Alternating(Alternating),
+}
+
+impl CrateMover for Crane {
+    fn carry(&mut self, stacks: &mut Stacks, op: Op) {
+        match self {
+            Crane::Mover9000 => CrateMover9000.carry(stacks, op),
+            Crane::Mover9001 => CrateMover9001.carry(stacks, op),
+            Crane::Capped(crane) => crane.carry(stacks, op),
+            Crane::Alternating(crane) => crane.carry(stacks, op),
+        }
+    }
+}
+
+impl FromStr for Crane {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            Some(("capped", capacity)) => match capacity.parse() {
+                Ok(capacity) if capacity > 0 => Ok(Crane::Capped(Capped { capacity })),
+                _ => Err(format!("invalid crane capacity {}", capacity)),
+            },
+            _ => match s {
+                "9000" => Ok(Crane::Mover9000),
+                "9001" => Ok(Crane::Mover9001),
+                "alternating" => Ok(Crane::Alternating(Alternating::default())),
+                _ => Err(format!("unknown crane model {}", s)),
+            },
+        }
+    }
+}
+
+/// Every state the stacks go through while applying a procedure, so it can be stepped
+/// through in either direction. Step 0 is the starting drawing, step `i` is the state after
+/// the `i`th op.
+struct Replay {
+    ops: Vec<Op>,
+    snapshots: Vec<Stacks>,
+    step: usize,
+}
+
+impl Replay {
+    fn new(
+        stacks: Stacks,
+        ops: Vec<Op>,
+        mover: &mut (impl CrateMover + ?Sized),
+    ) -> Result<Self, Error> {
+        let mut snapshots = vec![stacks];
+        for op in &ops {
+            let mut next = snapshots[snapshots.len() - 1].clone();
+            mover.apply(&mut next, *op)?;
+            snapshots.push(next);
+        }
+        Ok(Self {
+            ops,
+            snapshots,
+            step: 0,
+        })
+    }
+
+    /// Number of steps, i.e. the index of the final state.
+    fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    fn state_at(&self, step: usize) -> Option<&Stacks> {
+        self.snapshots.get(step)
+    }
+
+    /// Jumps to `step`. Returns false if there is no such step.
+    fn seek(&mut self, step: usize) -> bool {
+        let found = self.state_at(step).is_some();
+        if found {
+            self.step = step;
+        }
+        found
+    }
+
+    fn current(&self) -> &Stacks {
+        &self.snapshots[self.step]
+    }
+
+    /// Applies the next op. Returns false if already at the end.
+    fn forward(&mut self) -> bool {
+        let moved = self.step < self.len();
+        self.step += moved as usize;
+        moved
+    }
+
+    /// Undoes the last op. Returns false if already at the start.
+    fn back(&mut self) -> bool {
+        let moved = self.step > 0;
+        self.step -= moved as usize;
+        moved
+    }
+}
+
+/// The current step, the op that led to it and the resulting stacks.
+impl Display for Replay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.step {
+            0 => writeln!(f, "step 0/{}: start", self.len())?,
+            step => writeln!(f, "step {}/{}: {}", step, self.len(), self.ops[step - 1])?,
+        }
+        write!(f, "{}", self.current())
+    }
+}
+
+fn solve(input: &str, mover: &mut (impl CrateMover + ?Sized)) -> Result<String, Error> {
+    let (mut stacks, procedures) = parse_input(input)?;
     for p in procedures {
-        stacks.process1(p);
+        mover.apply(&mut stacks, p)?;
     }
+    Ok(stacks.tops())
+}
 
-    Some(stacks.tops())
+pub fn part_one(input: &str) -> Option<String> {
+    solve(input, &mut CrateMover9000).ok()
 }
 
 pub fn part_two(input: &str) -> Option<String> {
-    let (crates, procedure) = input
-        .split("\n\n")
-        .collect_tuple()
-        .expect("have crate and procedure");
-    let (_, mut stacks) = parse_crate_lines(crates).expect("parse stack error");
-    let (_, procedures) = parse_procedures(procedure).expect("parse procedure error");
+    solve(input, &mut CrateMover9001).ok()
+}
 
-    for p in procedures {
-        stacks.process2(p);
+/// Why `plan` found no procedure.
+#[derive(Debug, PartialEq, Eq)]
+enum NoPlan {
+    /// No procedure of at most `max_moves` ops spells the target.
+    Exhausted,
+    /// The search reached `max_states` arrangements before it could tell.
+    TooManyStates,
+}
+
+/// Finds a shortest procedure after which the tops of the stacks read `target`, trying at
+/// most `max_moves` ops and keeping at most `max_states` arrangements in memory. The search
+/// is breadth-first over the stacks together with the crane, since a crane like `Alternating`
+/// carries the same op differently depending on its history.
+fn plan<M: CrateMover + Clone + Eq + Hash>(
+    stacks: &Stacks,
+    target: &str,
+    mover: M,
+    max_moves: usize,
+    max_states: usize,
+) -> Result<Vec<Op>, NoPlan> {
+    if !feasible(stacks, target) {
+        return Err(NoPlan::Exhausted);
+    }
+    let mut seen = HashSet::from([(stacks.clone(), mover.clone())]);
+    // every state reached so far, with its predecessor and the op leading to it.
+    let mut nodes = vec![(stacks.clone(), mover, None)];
+    let mut layer = 0..1;
+    for depth in 0..=max_moves {
+        if let Some(mut i) = layer.clone().find(|&i| nodes[i].0.tops() == target) {
+            let mut ops = vec![];
+            while let Some((prev, op)) = nodes[i].2 {
+                ops.push(op);
+                i = prev;
+            }
+            ops.reverse();
+            return Ok(ops);
+        }
+        if depth == max_moves {
+            break;
+        }
+        for i in layer.clone() {
+            for op in nodes[i].0.moves().collect_vec() {
+                let (mut stacks, mut mover) = (nodes[i].0.clone(), nodes[i].1.clone());
+                mover.carry(&mut stacks, op);
+                if seen.insert((stacks.clone(), mover.clone())) {
+                    if nodes.len() == max_states {
+                        return Err(NoPlan::TooManyStates);
+                    }
+                    nodes.push((stacks, mover, Some((i, op))));
+                }
+            }
+        }
+        layer = layer.end..nodes.len();
+    }
+    Err(NoPlan::Exhausted)
+}
+
+/// Whether there are enough stacks and crates of each letter to spell `target` at all.
+fn feasible(stacks: &Stacks, target: &str) -> bool {
+    let crates = stacks.0.iter().flatten().map(|c| c.0).counts();
+    target.chars().count() <= stacks.0.len()
+        && target
+            .chars()
+            .counts()
+            .into_iter()
+            .all(|(c, n)| crates.get(&c).is_some_and(|&have| have >= n))
+}
+
+/// Interactive replay on stdin: enter or `n` steps forward, `p` steps back, a number jumps
+/// to that step and `q` quits.
+fn replay(mut replay: Replay) {
+    println!("{}", replay);
+    for line in io::stdin().lines() {
+        let line = line.expect("could not read stdin");
+        let moved = match line.trim() {
+            "" | "n" => replay.forward(),
+            "p" => replay.back(),
+            "q" => break,
+            step => step.parse().is_ok_and(|step| replay.seek(step)),
+        };
+        if moved {
+            println!("{}", replay);
+        } else {
+            println!("no such step, {} steps in total", replay.len());
+        }
     }
+}
+
+struct Args {
+    model: Option<Crane>,
+    plan: Option<String>,
+    max_moves: usize,
+    max_states: usize,
+    replay: bool,
+    step: Option<usize>,
+}
 
-    Some(stacks.tops())
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        model: args.opt_value_from_str("--model")?,
+        plan: args.opt_value_from_str("--plan")?,
+        max_moves: args.opt_value_from_str("--max-moves")?.unwrap_or(4),
+        max_states: args.opt_value_from_str("--max-states")?.unwrap_or(100_000),
+        replay: args.contains("--replay"),
+        step: args.opt_value_from_str("--step")?,
+    };
+    finish_args(args)?;
+    Ok(parsed)
 }
 
 fn main() {
+    let Args {
+        mut model,
+        plan: target,
+        max_moves,
+        max_states,
+        replay: replaying,
+        step,
+    } = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!(
+                "usage: `cargo solve 05 -- [--model 9000|9001|capped:N|alternating] \
+                 [--plan <tops> [--max-moves N] [--max-states N]] [--replay [--step N]]`"
+            );
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 5);
+    if let Some(target) = target {
+        let stacks = match parse_input(input) {
+            Ok((stacks, _)) => stacks,
+            Err(e) => return eprintln!("{}", e),
+        };
+        let mover = model.unwrap_or(Crane::Mover9000);
+        // printed as a puzzle input of its own.
+        match plan(&stacks, &target, mover, max_moves, max_states) {
+            Ok(ops) => print!(
+                "{}\n{}",
+                stacks,
+                ops.iter().map(|op| format!("{}\n", op)).join("")
+            ),
+            Err(NoPlan::Exhausted) => eprintln!(
+                "no procedure of at most {} moves spells {}",
+                max_moves, target
+            ),
+            Err(NoPlan::TooManyStates) => eprintln!(
+                "gave up after {} arrangements, try fewer --max-moves or more --max-states",
+                max_states
+            ),
+        }
+        return;
+    }
+    if replaying {
+        let mover = model.get_or_insert(Crane::Mover9000);
+        let state = parse_input(input).and_then(|(stacks, ops)| Replay::new(stacks, ops, mover));
+        let mut state = match state {
+            Ok(state) => state,
+            Err(e) => return eprintln!("{}", e),
+        };
+        match step {
+            Some(step) if state.seek(step) => print!("{}", state),
+            Some(_) => eprintln!("no such step, {} steps in total", state.len()),
+            None => replay(state),
+        }
+        return;
+    }
+    if let Some(mover) = &mut model {
+        match solve(input, mover) {
+            Ok(tops) => println!("{}", tops),
+            Err(e) => eprintln!("{}", e),
+        }
+        return;
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -171,4 +656,152 @@ mod tests {
         let input = advent_of_code::read_file("examples", 5);
         assert_eq!(part_two(&input), Some("MCD".to_string()));
     }
+
+    #[test]
+    fn test_render() {
+        let input = advent_of_code::read_file("examples", 5);
+        let (stacks, _) = parse_input(&input).unwrap();
+        assert_eq!(
+            stacks.to_string(),
+            "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n"
+        );
+        assert!(input.starts_with(&stacks.to_string()));
+    }
+
+    #[test]
+    fn test_replay() {
+        let input = advent_of_code::read_file("examples", 5);
+        let (stacks, ops) = parse_input(&input).unwrap();
+        let mut replay = Replay::new(stacks.clone(), ops, &mut CrateMover9000).unwrap();
+        assert_eq!(replay.len(), 4);
+        assert!(!replay.back());
+        while replay.forward() {}
+        assert_eq!(replay.current().tops(), "CMZ");
+        assert!(replay.back());
+        assert_eq!(replay.current().tops(), "MZ");
+        assert_eq!(replay.state_at(0), Some(&stacks));
+        assert_eq!(replay.state_at(5), None);
+        assert!(!replay.seek(5));
+        assert!(replay.seek(3));
+        assert!(replay
+            .to_string()
+            .starts_with("step 3/4: move 2 from 2 to 1\n"));
+    }
+
+    #[test]
+    fn test_movers() {
+        let input = advent_of_code::read_file("examples", 5);
+        let solve = |mover: &mut dyn CrateMover| solve(&input, mover).unwrap();
+        assert_eq!(solve(&mut Capped { capacity: 1 }), "CMZ");
+        assert_eq!(solve(&mut Capped { capacity: 3 }), "MCD");
+        assert_eq!(solve(&mut Capped { capacity: 2 }), "MCZ");
+        assert_eq!(solve(&mut Alternating::default()), "MCZ");
+        assert_eq!(solve(&mut "9001".parse::<Crane>().unwrap()), "MCD");
+        assert!("capped:0".parse::<Crane>().is_err());
+
+        // putting crates back where they were, one at a time or not, changes nothing.
+        let input = "[A]\n[B]\n[C]\n 1\n\nmove 2 from 1 to 1\n";
+        let solve = |mover: &mut dyn CrateMover| super::solve(input, mover).unwrap();
+        assert_eq!(solve(&mut CrateMover9000), "A");
+        assert_eq!(solve(&mut CrateMover9001), "A");
+        assert_eq!(solve(&mut Capped { capacity: 1 }), "A");
+        assert_eq!(solve(&mut Alternating { reverse: true }), "A");
+        assert!("9002".parse::<Crane>().is_err());
+    }
+
+    #[test]
+    fn test_parse() {
+        // trailing whitespace stripped by an editor.
+        let input = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3\n\nmove 1 from 2 to 1\n";
+        assert_eq!(part_one(input), Some("DCP".to_string()));
+
+        let wide = (1..=12).map(|i| format!("{:^3}", i)).join(" ");
+        let crates = (b'A'..=b'L').map(|c| format!("[{}]", c as char)).join(" ");
+        let input = format!("{}\n{}\n\nmove 1 from 12 to 10\n", crates, wide);
+        let (stacks, _) = parse_input(&input).unwrap();
+        assert_eq!(stacks.0.len(), 12);
+        assert_eq!(stacks.to_string(), format!("{}\n{}\n", crates, wide));
+        assert_eq!(part_two(&input), Some("ABCDEFGHILK".to_string()));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |input: &str| solve(input, &mut CrateMover9000).unwrap_err().to_string();
+        assert_eq!(
+            error("[A]\n 1   2\n\nmove 1 from 2 to 1"),
+            "line 4: move 1 from 2 to 1: stack 2 only has 0 crates"
+        );
+        assert_eq!(
+            error("[A]\n 1\n\nmove 1 from 1 to 1\nmove 1 from 1 to 3"),
+            "line 5: move 1 from 1 to 3: there is no stack 3"
+        );
+        assert_eq!(
+            error("[A]\n 1\n\nmove 1 from 1\n"),
+            "line 4: expected `move N from A to B`"
+        );
+        assert_eq!(
+            error("[A]\n 1   2   3\n\nmove 1 to 3 from 2\n"),
+            "line 4: expected `move N from A to B`"
+        );
+        assert_eq!(
+            error("[A] [B]\n 1\n\n"),
+            "line 1: crate in unlabeled stack 2"
+        );
+        assert_eq!(
+            error("[A]\n    [B]\n 1   2\n\n"),
+            "line 1: crate floating above an empty spot in stack 1"
+        );
+        assert_eq!(
+            error("[A]\n 1   3\n\n"),
+            "line 2: expected stack labels counting up from 1"
+        );
+        assert_eq!(
+            error("[A]\n 1"),
+            "line 2: expected a blank line followed by the procedure"
+        );
+    }
+
+    #[test]
+    fn test_plan() {
+        let check = |stacks: &Stacks, target, mut mover: Crane, len| {
+            let ops = plan(stacks, target, mover, 6, 100_000).unwrap();
+            assert_eq!(ops.len(), len);
+            let mut stacks = stacks.clone();
+            for op in ops {
+                mover.apply(&mut stacks, op).unwrap();
+            }
+            assert_eq!(stacks.tops(), target);
+        };
+
+        let (stacks, _) = parse_input("[B]\n[A]\n 1   2   3\n\n").unwrap();
+        check(&stacks, "AB", Crane::Mover9000, 1);
+        check(&stacks, "BA", Crane::Mover9000, 2);
+        check(&stacks, "BA", Crane::Mover9001, 2);
+        check(&stacks, "B", Crane::Mover9001, 0);
+        assert_eq!(
+            plan(&stacks, "ABC", Crane::Mover9000, 6, 100_000),
+            Err(NoPlan::Exhausted)
+        );
+        assert_eq!(
+            plan(&stacks, "AA", Crane::Mover9000, 6, 100_000),
+            Err(NoPlan::Exhausted)
+        );
+
+        let input = advent_of_code::read_file("examples", 5);
+        let (stacks, _) = parse_input(&input).unwrap();
+        // the puzzle's own procedure takes four moves.
+        let ops = plan(&stacks, "CMZ", CrateMover9000, 4, 100_000).unwrap();
+        assert!(ops.len() <= 4);
+        check(&stacks, "CMZ", Crane::Mover9000, ops.len());
+        check(
+            &stacks,
+            "CMZ",
+            Crane::Alternating(Alternating::default()),
+            3,
+        );
+        assert_eq!(
+            plan(&stacks, "CMZ", CrateMover9000, 4, 10),
+            Err(NoPlan::TooManyStates)
+        );
+    }
 }
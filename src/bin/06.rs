@@ -1,25 +1,156 @@
-use std::collections::HashSet;
+use advent_of_code::helpers::finish_args;
+use std::{
+    fs::File,
+    io::{self, BufReader, Bytes, Read},
+    process,
+};
+
+/// Sliding window over the signal that knows in O(1) per byte whether its last `window`
+/// bytes are all different: `counts` holds how often each byte occurs in the window and
+/// `dups` how many byte values occur more than once.
+struct Detector {
+    counts: [u32; 256],
+    dups: usize,
+    ring: Vec<u8>,
+    len: usize,
+}
+
+impl Detector {
+    fn new(window: usize) -> Self {
+        assert!(window > 0, "window must not be empty");
+        Self {
+            counts: [0; 256],
+            dups: 0,
+            ring: vec![0; window],
+            len: 0,
+        }
+    }
+
+    /// Slides the window over `byte`. Returns whether the window now is a marker.
+    fn push(&mut self, byte: u8) -> bool {
+        let window = self.ring.len();
+        let slot = self.len % window;
+        if self.len >= window {
+            let old = self.ring[slot] as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 1 {
+                self.dups -= 1;
+            }
+        }
+        self.ring[slot] = byte;
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 2 {
+            self.dups += 1;
+        }
+        self.len += 1;
+        self.len >= window && self.dups == 0
+    }
+}
+
+/// Positions (number of bytes read) at which the last `window` bytes of a stream are all
+/// different. The signal ends at the first newline or at the end of the stream.
+struct Markers<R: Read> {
+    bytes: Bytes<BufReader<R>>,
+    detector: Detector,
+}
+
+impl<R: Read> Markers<R> {
+    fn new(reader: R, window: usize) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes(),
+            detector: Detector::new(window),
+        }
+    }
+}
+
+impl<R: Read> Iterator for Markers<R> {
+    type Item = io::Result<usize>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.bytes.next()? {
+                Ok(b'\n') => return None,
+                Ok(byte) if self.detector.push(byte) => return Some(Ok(self.detector.len)),
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+fn first_marker(input: &str, window: usize) -> Option<u32> {
+    Markers::new(input.as_bytes(), window)
+        .next()
+        .and_then(Result::ok)
+        .map(|pos| pos as u32)
+}
 
 pub fn part_one(input: &str) -> Option<u32> {
-    input
-        .as_bytes()
-        .windows(4)
-        .enumerate()
-        .find(|(_, w)| w.iter().collect::<HashSet<_>>().len() == 4)
-        .map(|(i, _)| i as u32 + 1 + 3)
+    first_marker(input, 4)
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    input
-        .as_bytes()
-        .windows(14)
-        .enumerate()
-        .find(|(_, w)| w.iter().collect::<HashSet<_>>().len() == 14)
-        .map(|(i, _)| i as u32 + 1 + 13)
+    first_marker(input, 14)
+}
+
+struct Args {
+    window: Option<usize>,
+    all: bool,
+    file: Option<String>,
+}
+
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        window: args.opt_value_from_str("--window")?,
+        all: args.contains("--all"),
+        file: args.opt_value_from_str("--file")?,
+    };
+    finish_args(args)?;
+    Ok(parsed)
 }
 
 fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!("usage: `cargo solve 06 -- [--window N [--all] [--file path]]`");
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 6);
+    if let Some(window) = args.window {
+        if window == 0 {
+            eprintln!("a marker needs a window of at least one byte");
+            process::exit(1);
+        }
+        let reader: Box<dyn Read> = match args.file {
+            Some(path) => match File::open(&path) {
+                Ok(file) => Box::new(file),
+                Err(e) => {
+                    eprintln!("could not open {}: {}", path, e);
+                    process::exit(1);
+                }
+            },
+            None => Box::new(input.as_bytes()),
+        };
+        let mut markers = Markers::new(reader, window);
+        let markers = if args.all {
+            markers.collect::<io::Result<Vec<_>>>()
+        } else {
+            markers.next().transpose().map(Vec::from_iter)
+        };
+        match markers {
+            Ok(markers) if markers.is_empty() => println!("no marker of size {}", window),
+            Ok(markers) => markers.iter().for_each(|pos| println!("{}", pos)),
+            Err(e) => {
+                eprintln!("could not read signal: {}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -27,6 +158,8 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::check::{self, Check};
+    use std::collections::HashSet;
 
     #[test]
     fn test_part_one() {
@@ -39,4 +172,53 @@ mod tests {
         let input = advent_of_code::read_file("examples", 6);
         assert_eq!(part_two(&input), Some(19));
     }
+
+    fn reference_first_marker(input: &str, window: usize) -> Option<u32> {
+        let signal = input.lines().next()?.as_bytes();
+        signal
+            .windows(window)
+            .position(|w| w.iter().collect::<HashSet<_>>().len() == window)
+            .map(|i| (i + window) as u32)
+    }
+
+    fn reference_part_one(input: &str) -> Option<u32> {
+        reference_first_marker(input, 4)
+    }
+
+    fn reference_part_two(input: &str) -> Option<u32> {
+        reference_first_marker(input, 14)
+    }
+
+    #[test]
+    fn test_against_reference() {
+        check::cross_check(
+            6,
+            200,
+            500,
+            &[
+                Check::new("part one", part_one, reference_part_one),
+                Check::new("part two", part_two, reference_part_two),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_all_markers() {
+        let input = advent_of_code::read_file("examples", 6);
+        let signal = input.trim_end().as_bytes();
+        for window in 1..=16 {
+            let expected = signal
+                .windows(window)
+                .enumerate()
+                .filter(|(_, w)| (1..w.len()).all(|i| !w[..i].contains(&w[i])))
+                .map(|(i, _)| i + window)
+                .collect::<Vec<_>>();
+            let markers = Markers::new(input.as_bytes(), window)
+                .collect::<io::Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(markers, expected, "window {}", window);
+        }
+        assert_eq!(first_marker("bvwbjplbgvbhsrlpgdmjqwftvncz", 4), Some(5));
+        assert_eq!(first_marker("aaaa\nbcde", 4), None);
+    }
 }
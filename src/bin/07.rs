@@ -1,240 +1,523 @@
+use advent_of_code::helpers::finish_args;
 use std::{
-    cell::{Ref, RefCell, RefMut},
-    collections::HashMap,
-    fmt::Debug,
-    ops::Deref,
-    rc::{Rc, Weak},
+    cell::Cell,
+    collections::BTreeMap,
+    fmt::{self, Debug, Display, Write},
+    fs, process,
     str::FromStr,
 };
 
 use nom::{
     branch::alt,
-    bytes::complete::{tag, take_while},
-    character::complete::{alpha1, digit1, space1},
-    combinator::{map, map_res},
+    bytes::complete::tag,
+    character::complete::{digit1, space1},
+    combinator::{all_consuming, map, map_res, rest, verify},
     sequence::{preceded, tuple},
     IResult,
 };
+use serde::{Deserialize, Serialize};
 
 trait Size {
     fn size(&self) -> u32;
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct NodeId(usize);
+
+#[derive(Debug)]
+struct Node {
+    entry: Entry,
+    parent: Option<NodeId>,
+    children: BTreeMap<String, NodeId>,
+    size: Cell<Option<u32>>,
+}
+
+impl Node {
+    fn new(entry: Entry, parent: Option<NodeId>) -> Self {
+        Self {
+            entry,
+            parent,
+            children: BTreeMap::new(),
+            size: Cell::new(None),
+        }
+    }
+}
+
+/// Filesystem tree stored in an arena. Nodes are addressed by `NodeId` and never removed.
 #[derive(Debug)]
 struct FS {
-    root: NodePtr<Entry>,
-    cur: NodePtr<Entry>,
+    nodes: Vec<Node>,
+    cur: NodeId,
 }
 
 impl FS {
+    const ROOT: NodeId = NodeId(0);
+
     fn new() -> Self {
-        let root = Node::new(Entry::root(), HashMap::new(), None);
-        let root: NodePtr<_> = Rc::new(RefCell::new(root)).into();
-        let cur = root.clone();
-        Self { root, cur }
-    }
-
-    fn cd(&mut self, path: &str) {
-        match path {
-            "/" => self.cur = self.root.clone(),
-            ".." => {
-                let parent = self.cur.borrow().parent.clone().expect("no parent");
-                self.cur = parent.into();
-            }
-            s => {
-                let cur = self.cur.borrow();
-                let target = cur
-                    .children
-                    .iter()
-                    .find(|(name, e)| name.as_str() == s && matches!(e.borrow().val, Entry::Dir(_)))
-                    .expect("no dir")
-                    .1
-                    .clone();
-
-                drop(cur);
-                self.cur = target;
-            }
+        Self {
+            nodes: vec![Node::new(Entry::root(), None)],
+            cur: Self::ROOT,
         }
     }
 
-    fn insert(&mut self, entry: Entry) {
-        let name = entry.name();
-        let mut cur = self.cur.borrow_mut();
-        cur.children.insert(
-            name.to_string(),
-            NodePtr::from_node(Node::new(entry, HashMap::new(), Some(self.cur.clone()))),
-        );
+    fn root(&self) -> NodeId {
+        Self::ROOT
     }
-}
 
-impl IntoIterator for FS {
-    type Item = NodePtr<Entry>;
-    type IntoIter = NodeIter<Entry>;
+    fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id.0]
+    }
 
-    fn into_iter(self) -> Self::IntoIter {
-        NodeIter::new(self.root)
+    fn entry(&self, id: NodeId) -> &Entry {
+        &self.node(id).entry
     }
-}
 
-#[derive(Debug)]
-struct NodePtr<T>(Rc<RefCell<Node<T>>>);
+    fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).parent
+    }
 
-impl<T> NodePtr<T> {
-    fn is_leaf(&self) -> bool {
-        self.borrow().is_leaf()
+    fn children(&self, id: NodeId) -> impl DoubleEndedIterator<Item = NodeId> + '_ {
+        self.node(id).children.values().copied()
     }
 
-    fn from_node(node: Node<T>) -> Self {
-        Rc::new(RefCell::new(node)).into()
+    fn child(&self, id: NodeId, name: &str) -> Option<NodeId> {
+        self.node(id).children.get(name).copied()
     }
 
-    fn into_weak(self) -> NodeWeakPtr<T> {
-        Rc::downgrade(&self.0)
+    fn is_dir(&self, id: NodeId) -> bool {
+        matches!(self.entry(id), Entry::Dir(_))
     }
 
-    fn val(&self) -> Ref<T> {
-        Ref::map(self.0.borrow(), |v| &v.val)
+    fn path(&self, id: NodeId) -> String {
+        let mut names = std::iter::successors(Some(id), |&p| self.parent(p))
+            .filter(|&id| id != self.root())
+            .map(|id| self.entry(id).name())
+            .collect::<Vec<_>>();
+        names.reverse();
+        format!("/{}", names.join("/"))
     }
 
-    fn val_mut(&self) -> RefMut<T> {
-        RefMut::map(self.0.borrow_mut(), |v| &mut v.val)
+    /// Deepest directory that `--from-json` can read back: every directory nests an object
+    /// and an array, and serde_json gives up at 128 levels.
+    const MAX_JSON_DEPTH: usize = 62;
+
+    /// Fails on subtrees nested too deep to be imported again.
+    fn export(&self, id: NodeId) -> Result<JsonEntry, String> {
+        let too_deep = self
+            .preorder(id)
+            .with_depth()
+            .any(|(id, depth)| depth > Self::MAX_JSON_DEPTH && self.is_dir(id));
+        if too_deep {
+            return Err(format!(
+                "directories are nested more than {} deep",
+                Self::MAX_JSON_DEPTH
+            ));
+        }
+        // the children of a directory come out of the walk right before it.
+        let mut done = vec![];
+        for id in self.postorder(id) {
+            let entry = match self.entry(id) {
+                Entry::File(f) => JsonEntry::File {
+                    name: f.name.clone(),
+                    size: f.size(),
+                },
+                Entry::Dir(d) => JsonEntry::Dir {
+                    name: d.name.clone(),
+                    children: done.split_off(done.len() - self.node(id).children.len()),
+                },
+            };
+            done.push(entry);
+        }
+        Ok(done.remove(0))
     }
 
-    fn set_seen(&self) {
-        self.borrow_mut().set_seen();
+    /// Fails on names that would not survive a round trip through `transcript`. Recursive,
+    /// but serde_json never nests a `JsonEntry` more than `MAX_JSON_DEPTH` directories deep.
+    fn import(&mut self, parent: NodeId, json: &JsonEntry) -> Result<(), String> {
+        let (JsonEntry::File { name, .. } | JsonEntry::Dir { name, .. }) = json;
+        if !is_entry_name(name) {
+            return Err(format!("invalid name {:?} in {}", name, self.path(parent)));
+        }
+        if self.child(parent, name).is_some() {
+            return Err(format!(
+                "duplicate name `{}` in {}",
+                name,
+                self.path(parent)
+            ));
+        }
+        match json {
+            JsonEntry::File { name, size } => {
+                self.push(parent, Entry::File(File::new(name, *size)));
+            }
+            JsonEntry::Dir { name, children } => {
+                let id = self.push(parent, Entry::Dir(Directory::new(name.clone())));
+                for child in children {
+                    self.import(id, child)?;
+                }
+            }
+        }
+        Ok(())
     }
 
-    fn is_seen(&self) -> bool {
-        self.borrow().seen
+    /// Writes a `$ cd`/`$ ls` session that reconstructs this tree when parsed.
+    fn transcript(&self) -> String {
+        let mut out = String::from("$ cd /\n");
+        self.write_transcript(self.root(), &mut out);
+        out
     }
-}
 
-impl NodePtr<Entry> {
-    fn size(&self) -> u32 {
-        match self.val().deref() {
-            Entry::File(s) => s.size(),
-            Entry::Dir(_) => self
-                .borrow()
-                .children
-                .values()
-                .map(|e| e.size())
-                .sum::<u32>(),
+    fn write_transcript(&self, id: NodeId, out: &mut String) {
+        out.push_str("$ ls\n");
+        for child in self.children(id) {
+            match self.entry(child) {
+                Entry::Dir(d) => writeln!(out, "dir {}", d.name),
+                Entry::File(f) => writeln!(out, "{} {}", f.size(), f.name),
+            }
+            .unwrap();
+        }
+        for child in self.children(id).filter(|&c| self.is_dir(c)) {
+            writeln!(out, "$ cd {}", self.entry(child).name()).unwrap();
+            self.write_transcript(child, out);
+            out.push_str("$ cd ..\n");
         }
     }
 
-    fn is_dir(&self) -> bool {
-        matches!(self.val().deref(), Entry::Dir(_))
+    /// Builds a complete tree of the given depth where every directory has `fanout`
+    /// subdirectories and `fanout` files. Useful for producing large inputs. File sizes shrink
+    /// as the tree grows, so the total always fits in a `u32`.
+    fn synthesize(depth: u32, fanout: u32) -> Result<Self, String> {
+        const MAX_DEPTH: u32 = 64;
+        const MAX_FILES: u64 = 1_000_000;
+
+        fn fill(fs: &mut FS, parent: NodeId, depth: u32, fanout: u32, max_size: u64) {
+            for i in 0..fanout {
+                let size = (fs.nodes.len() as u64 * 7919) % max_size + 1;
+                fs.push(
+                    parent,
+                    Entry::File(File::new(format!("f{}.txt", i), size as u32)),
+                );
+                if depth > 0 {
+                    let dir = fs.push(parent, Entry::Dir(Directory::new(format!("d{}", i))));
+                    fill(fs, dir, depth - 1, fanout, max_size);
+                }
+            }
+        }
+
+        // every level has `fanout` times the directories of the one above it.
+        let files = (0..=depth.min(MAX_DEPTH))
+            .try_fold((1u64, 0u64), |(level, dirs), _| {
+                let dirs = dirs + level;
+                (dirs <= MAX_FILES).then(|| (level * fanout as u64, dirs))
+            })
+            .map(|(_, dirs)| dirs * fanout as u64)
+            .filter(|&files| depth <= MAX_DEPTH && files <= MAX_FILES)
+            .ok_or_else(|| {
+                format!(
+                    "depth {} with fanout {} makes more than {} files or {} levels",
+                    depth, fanout, MAX_FILES, MAX_DEPTH
+                )
+            })?;
+        let max_size = (u32::MAX as u64 / files.max(1)).min(300_000);
+
+        let mut fs = FS::new();
+        fill(&mut fs, Self::ROOT, depth, fanout, max_size);
+        Ok(fs)
     }
 
-    fn is_file(&self) -> bool {
-        matches!(self.val().deref(), Entry::File(_))
+    /// Changes the current directory, creating directories that were not listed yet.
+    fn cd(&mut self, path: &str) -> Result<(), ParseErrorKind> {
+        if path.starts_with('/') {
+            self.cur = self.root();
+        }
+        for name in path.split('/').filter(|s| !s.is_empty()) {
+            self.cur = match name {
+                "." => self.cur,
+                ".." => self.parent(self.cur).unwrap_or(self.cur),
+                name => self.mkdir(self.cur, name)?,
+            };
+        }
+        Ok(())
     }
-}
 
-impl<T> Deref for NodePtr<T> {
-    type Target = Rc<RefCell<Node<T>>>;
-    fn deref(&self) -> &Self::Target {
-        &self.0
+    fn mkdir(&mut self, parent: NodeId, name: &str) -> Result<NodeId, ParseErrorKind> {
+        match self.child(parent, name) {
+            Some(id) if self.is_dir(id) => Ok(id),
+            Some(_) => Err(ParseErrorKind::NotADirectory(name.to_string())),
+            None => Ok(self.push(parent, Entry::Dir(Directory::new(name.to_string())))),
+        }
+    }
+
+    /// Adds an entry to the current directory. Listing the same entry again updates it in place.
+    fn insert(&mut self, entry: Entry) -> NodeId {
+        if let Some(id) = self.child(self.cur, entry.name()) {
+            match (&mut self.nodes[id.0].entry, &entry) {
+                (Entry::Dir(_), Entry::Dir(_)) => return id,
+                (Entry::File(old), Entry::File(new)) => {
+                    old.size = new.size;
+                    self.invalidate(id);
+                    return id;
+                }
+                // the entry changed its type: replace it.
+                _ => {}
+            }
+        }
+        self.push(self.cur, entry)
     }
-}
 
-impl<T> Clone for NodePtr<T> {
-    fn clone(&self) -> Self {
-        Self(self.0.clone())
+    fn push(&mut self, parent: NodeId, entry: Entry) -> NodeId {
+        let id = NodeId(self.nodes.len());
+        let name = entry.name().to_string();
+        self.nodes.push(Node::new(entry, Some(parent)));
+        self.nodes[parent.0].children.insert(name, id);
+        self.invalidate(parent);
+        id
+    }
+
+    fn invalidate(&self, id: NodeId) {
+        // caching a size caches its whole subtree, so above an uncached node nothing is cached.
+        let mut cur = Some(id);
+        while let Some(id) = cur {
+            if self.node(id).size.take().is_none() {
+                break;
+            }
+            cur = self.parent(id);
+        }
+    }
+
+    /// Cumulative size of a node, cached until something is inserted below it. Computed
+    /// bottom-up rather than recursively, so any depth of directories works, and without
+    /// walking into subtrees that are cached already.
+    fn size(&self, id: NodeId) -> u32 {
+        // like `postorder`, but skipping cached nodes.
+        let mut stack = vec![(id, false)];
+        while let Some((id, expanded)) = stack.pop() {
+            let node = self.node(id);
+            if node.size.get().is_some() {
+                continue;
+            }
+            if !expanded {
+                stack.push((id, true));
+                stack.extend(self.children(id).map(|c| (c, false)));
+                continue;
+            }
+            let size = match &node.entry {
+                Entry::File(f) => f.size(),
+                // every child was cached before its parent came up again.
+                Entry::Dir(_) => self
+                    .children(id)
+                    .filter_map(|c| self.node(c).size.get())
+                    .sum(),
+            };
+            node.size.set(Some(size));
+        }
+        self.node(id).size.get().unwrap_or_default()
+    }
+
+    /// Resolves an absolute path like `/a/e`. Relative paths are resolved from the root.
+    fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.split('/')
+            .filter(|s| !s.is_empty())
+            .try_fold(self.root(), |id, name| self.child(id, name))
     }
-}
 
-impl<T> From<Rc<RefCell<Node<T>>>> for NodePtr<T> {
-    fn from(n: Rc<RefCell<Node<T>>>) -> Self {
-        Self(n)
+    fn preorder(&self, id: NodeId) -> PreOrder<'_> {
+        PreOrder {
+            fs: self,
+            stack: vec![(id, 0)],
+            max_depth: usize::MAX,
+        }
+    }
+
+    /// Pre-order walk that does not descend more than `max_depth` levels below `id`.
+    fn depth_limited(&self, id: NodeId, max_depth: usize) -> PreOrder<'_> {
+        PreOrder {
+            max_depth,
+            ..self.preorder(id)
+        }
+    }
+
+    fn postorder(&self, id: NodeId) -> PostOrder<'_> {
+        PostOrder {
+            fs: self,
+            stack: vec![(id, false)],
+        }
     }
 }
 
-struct NodeIter<T> {
-    cur: NodePtr<T>,
-    has_next: bool,
+/// Serialized form of a tree: `{"type": "dir", "name": "/", "children": [...]}`.
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum JsonEntry {
+    Dir {
+        name: String,
+        children: Vec<JsonEntry>,
+    },
+    File {
+        name: String,
+        size: u32,
+    },
 }
 
-impl<T> NodeIter<T> {
-    pub fn new(cur: NodePtr<T>) -> Self {
-        Self {
-            cur,
-            has_next: true,
-        }
+impl TryFrom<&FS> for JsonEntry {
+    type Error = String;
+
+    fn try_from(fs: &FS) -> Result<Self, Self::Error> {
+        fs.export(fs.root())
     }
 }
 
-impl<T: Debug> NodeIter<T> {
-    fn move_to_parent(&mut self) {
-        let parent = self.cur.borrow().parent.clone();
-        if let Some(parent) = parent {
-            self.cur = parent.into();
-        } else {
-            self.has_next = false;
+impl TryFrom<&JsonEntry> for FS {
+    type Error = String;
+
+    fn try_from(json: &JsonEntry) -> Result<Self, Self::Error> {
+        let JsonEntry::Dir { children, .. } = json else {
+            return Err("root must be a directory".into());
+        };
+        let mut fs = FS::new();
+        for child in children {
+            fs.import(fs.root(), child)?;
         }
+        let used = fs.nodes.iter().map(|node| match &node.entry {
+            Entry::File(f) => f.size() as u64,
+            Entry::Dir(_) => 0,
+        });
+        if used.sum::<u64>() > u32::MAX as u64 {
+            return Err(format!("file sizes add up to more than {}", u32::MAX));
+        }
+        Ok(fs)
     }
+}
 
-    fn find_not_seen_children(&self) -> Option<NodePtr<T>> {
-        let cur = self.cur.clone();
-        let next = cur
-            .borrow()
-            .children
-            .iter()
-            .find(|(_, v)| !v.is_seen())
-            .map(|(_, v)| v.clone());
+/// Renders a subtree the way the puzzle statement draws it.
+struct Tree<'a> {
+    fs: &'a FS,
+    id: NodeId,
+}
 
-        next
+impl Display for Tree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (id, depth) in self.fs.preorder(self.id).with_depth() {
+            let indent = "  ".repeat(depth);
+            match self.fs.entry(id) {
+                Entry::Dir(d) => writeln!(f, "{}- {} (dir)", indent, d.name)?,
+                Entry::File(file) => {
+                    writeln!(f, "{}- {} (file, size={})", indent, file.name, file.size())?
+                }
+            }
+        }
+        Ok(())
     }
 }
 
-impl<T: Debug> Iterator for NodeIter<T> {
-    type Item = NodePtr<T>;
+/// `du -h`-style listing of every directory in a subtree, largest first.
+struct DiskUsage<'a> {
+    fs: &'a FS,
+    id: NodeId,
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        if !self.has_next {
-            None
-        } else {
-            let child = self.find_not_seen_children();
-            if let Some(child) = child {
-                self.cur = child;
-                self.next()
-            } else if !self.cur.is_seen() {
-                self.cur.set_seen();
-                Some(self.cur.clone())
-            } else {
-                self.move_to_parent();
-                self.next()
+impl Display for DiskUsage<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut dirs: Vec<(u32, NodeId, String)> = vec![];
+        // indices into `dirs` of the directories above the current one, so paths are built
+        // from their parent's rather than by walking up to the root every time.
+        let mut ancestors: Vec<usize> = vec![];
+        for (id, depth) in self.fs.preorder(self.id).with_depth() {
+            if !self.fs.is_dir(id) {
+                continue;
             }
+            ancestors.truncate(depth);
+            let path = match ancestors.last() {
+                Some(&parent) => {
+                    let parent: &str = &dirs[parent].2;
+                    format!(
+                        "{}/{}",
+                        parent.trim_end_matches('/'),
+                        self.fs.entry(id).name()
+                    )
+                }
+                None => self.fs.path(id),
+            };
+            ancestors.push(dirs.len());
+            dirs.push((self.fs.size(id), id, path));
         }
+        dirs.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+        for (size, _, path) in dirs {
+            writeln!(f, "{:<6}{}", human_size(size), path)?;
+        }
+        Ok(())
     }
 }
 
-type NodeWeakPtr<T> = Weak<RefCell<Node<T>>>;
+/// Formats a byte count like `du -h`: at most three significant digits, rounded up.
+fn human_size(size: u32) -> String {
+    const UNITS: [&str; 4] = ["K", "M", "G", "T"];
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut scaled = size as f64;
+    let mut unit = "";
+    for u in UNITS {
+        if scaled < 1024.0 {
+            break;
+        }
+        scaled /= 1024.0;
+        unit = u;
+    }
+    if scaled < 10.0 {
+        format!("{:.1}{}", (scaled * 10.0).ceil() / 10.0, unit)
+    } else {
+        format!("{}{}", scaled.ceil(), unit)
+    }
+}
 
-#[derive(Debug)]
-struct Node<T> {
-    val: T,
-    seen: bool,
-    children: HashMap<String, NodePtr<T>>,
-    parent: Option<NodePtr<T>>,
+struct PreOrder<'a> {
+    fs: &'a FS,
+    stack: Vec<(NodeId, usize)>,
+    max_depth: usize,
 }
 
-impl<T> Node<T> {
-    fn new(val: T, children: HashMap<String, NodePtr<T>>, parent: Option<NodePtr<T>>) -> Self {
-        Self {
-            val,
-            seen: false,
-            children,
-            parent,
+impl<'a> PreOrder<'a> {
+    /// Yields each node together with its depth below the node the walk started at.
+    fn with_depth(mut self) -> impl Iterator<Item = (NodeId, usize)> + 'a {
+        std::iter::from_fn(move || self.next_with_depth())
+    }
+
+    fn next_with_depth(&mut self) -> Option<(NodeId, usize)> {
+        let (id, depth) = self.stack.pop()?;
+        if depth < self.max_depth {
+            self.stack
+                .extend(self.fs.children(id).rev().map(|c| (c, depth + 1)));
         }
+        Some((id, depth))
     }
+}
+
+impl Iterator for PreOrder<'_> {
+    type Item = NodeId;
 
-    fn is_leaf(&self) -> bool {
-        self.children.is_empty()
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_with_depth().map(|(id, _)| id)
     }
+}
+
+struct PostOrder<'a> {
+    fs: &'a FS,
+    stack: Vec<(NodeId, bool)>,
+}
+
+impl Iterator for PostOrder<'_> {
+    type Item = NodeId;
 
-    fn set_seen(&mut self) {
-        self.seen = true;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (id, expanded) = self.stack.pop()?;
+            if expanded {
+                return Some(id);
+            }
+            self.stack.push((id, true));
+            self.stack
+                .extend(self.fs.children(id).rev().map(|c| (c, false)));
+        }
     }
 }
 
@@ -319,12 +602,38 @@ enum Command {
     Ls,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+struct ParseError {
+    line: usize,
+    kind: ParseErrorKind,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ParseErrorKind {
+    UnknownCommand(String),
+    UnexpectedOutput(String),
+    NotADirectory(String),
+    TooLarge,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ParseErrorKind::UnknownCommand(s) => write!(f, "unknown command `{}`", s),
+            ParseErrorKind::UnexpectedOutput(s) => write!(f, "unexpected output `{}`", s),
+            ParseErrorKind::NotADirectory(s) => write!(f, "`{}` is not a directory", s),
+            ParseErrorKind::TooLarge => write!(f, "file sizes add up to more than {}", u32::MAX),
+        }
+    }
+}
+
 fn parse_command_line(s: &str) -> IResult<&str, Command> {
-    preceded(tag("$ "), alt((parse_cd, parse_ls)))(s)
+    all_consuming(preceded(tag("$ "), alt((parse_cd, parse_ls))))(s)
 }
 
 fn parse_cd(s: &str) -> IResult<&str, Command> {
-    map(preceded(tag("cd "), parse_ident), |s: &str| {
+    map(preceded(tag("cd "), parse_name), |s: &str| {
         Command::Cd(s.into())
     })(s)
 }
@@ -334,16 +643,27 @@ fn parse_ls(s: &str) -> IResult<&str, Command> {
 }
 
 fn parse_dir_name(s: &str) -> IResult<&str, &str> {
-    preceded(tag("dir "), alpha1)(s)
+    preceded(tag("dir "), parse_entry_name)(s)
+}
+
+/// Paths run until the end of the line and may contain anything, including spaces.
+fn parse_name(s: &str) -> IResult<&str, &str> {
+    verify(rest, |s: &str| !s.is_empty())(s)
+}
+
+/// Whether `name` can be listed by `ls`: `$ cd` has to be able to get back into it, so it
+/// cannot contain `/` or be `.` or `..`.
+fn is_entry_name(name: &str) -> bool {
+    !matches!(name, "" | "." | "..") && !name.contains(['/', '\n', '\r'])
 }
 
-fn parse_ident(s: &str) -> IResult<&str, &str> {
-    take_while(|v: char| !v.is_whitespace())(s)
+fn parse_entry_name(s: &str) -> IResult<&str, &str> {
+    verify(rest, is_entry_name)(s)
 }
 
 fn parse_file(s: &str) -> IResult<&str, (u32, String)> {
     map_res(
-        tuple((digit1, space1, parse_ident)),
+        tuple((digit1, space1, parse_entry_name)),
         |(size, _, name): (&str, &str, &str)| {
             Ok::<_, <u32 as FromStr>::Err>((size.parse()?, name.into()))
         },
@@ -351,77 +671,451 @@ fn parse_file(s: &str) -> IResult<&str, (u32, String)> {
 }
 
 fn parse_ls_result(s: &str) -> IResult<&str, LsResult> {
-    alt((
+    all_consuming(alt((
         map(parse_file, |(s, n)| LsResult::File(s, n)),
         map(parse_dir_name, |n| LsResult::Dir(n.into())),
-    ))(s)
+    )))(s)
 }
 
-fn parse_input(s: &str) -> IResult<&str, FS> {
+fn parse_input(s: &str) -> Result<FS, ParseError> {
     let mut fs = FS::new();
-    let mut lines = s.lines().peekable();
-    loop {
-        let Some(s) = lines.next() else {
-            break;
+    let mut listing = false;
+    // total size of the files listed so far, which bounds the size of every directory.
+    let mut used = 0u32;
+    for (idx, line) in s.lines().enumerate() {
+        let error = |kind| ParseError {
+            line: idx + 1,
+            kind,
         };
+        if line.trim().is_empty() {
+            continue;
+        }
 
-        let (remain, command) = parse_command_line(s)?;
-        assert!(remain.is_empty());
-        match command {
-            Command::Cd(dir) => fs.cd(&dir),
-            Command::Ls => {
-                let mut results = Vec::new();
-                while let Some(s) = lines.peek() {
-                    match parse_ls_result(s) {
-                        Ok(result) => {
-                            results.push(result.1);
-                            lines.next();
-                        }
-                        Err(_) => break,
-                    }
-                }
-                for result in results.into_iter().map(Into::into) {
-                    fs.insert(result);
+        if line.starts_with('$') {
+            let (_, command) = parse_command_line(line)
+                .map_err(|_| error(ParseErrorKind::UnknownCommand(line.into())))?;
+            listing = matches!(command, Command::Ls);
+            if let Command::Cd(path) = command {
+                fs.cd(&path).map_err(error)?;
+            }
+        } else {
+            let result = match parse_ls_result(line) {
+                Ok((_, result)) if listing => result,
+                _ => return Err(error(ParseErrorKind::UnexpectedOutput(line.into()))),
+            };
+            if let LsResult::File(size, name) = &result {
+                let replaced = fs
+                    .child(fs.cur, name)
+                    .filter(|&id| !fs.is_dir(id))
+                    .map_or(0, |id| fs.size(id));
+                used = (used - replaced)
+                    .checked_add(*size)
+                    .ok_or_else(|| error(ParseErrorKind::TooLarge))?;
+            }
+            fs.insert(result.into());
+        }
+    }
+    Ok(fs)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Disk {
+    total: u32,
+    needed: u32,
+}
+
+impl Default for Disk {
+    fn default() -> Self {
+        Self {
+            total: 70000000,
+            needed: 30000000,
+        }
+    }
+}
+
+impl Disk {
+    fn shortfall(&self, used: u32) -> u32 {
+        self.needed.saturating_sub(self.total.saturating_sub(used))
+    }
+}
+
+/// How to pick directories for deletion. Only directories below `/` are considered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Policy {
+    /// The single smallest directory that frees enough space.
+    SmallestDir,
+    /// Non-nested directories that together free enough space, freeing as little as possible.
+    MinimalSet,
+    /// As few non-nested directories as possible, then as little freed space as possible.
+    FewestDirs,
+}
+
+impl FromStr for Policy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smallest" => Ok(Policy::SmallestDir),
+            "minimal" => Ok(Policy::MinimalSet),
+            "fewest" => Ok(Policy::FewestDirs),
+            _ => Err(format!("unknown policy `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Plan {
+    dirs: Vec<NodeId>,
+    freed: u32,
+    /// False if the search gave up early, so a better plan may exist.
+    complete: bool,
+}
+
+fn plan(fs: &FS, disk: Disk, policy: Policy) -> Option<Plan> {
+    let need = disk.shortfall(fs.size(fs.root())) as usize;
+    if need == 0 {
+        return Some(Plan {
+            dirs: vec![],
+            freed: 0,
+            complete: true,
+        });
+    }
+
+    let candidates = Candidates::new(fs);
+    if candidates.rest[0] < need {
+        return None;
+    }
+    let smallest = (0..candidates.dirs.len())
+        .filter(|&i| candidates.sizes[i] >= need)
+        .min_by_key(|&i| candidates.sizes[i]);
+
+    let (picked, complete) = match (policy, smallest) {
+        (Policy::SmallestDir, _) => (smallest.map(|i| vec![i]), true),
+        // with a single directory, the fewest is the smallest one.
+        (Policy::FewestDirs, Some(i)) => (Some(vec![i]), true),
+        _ => {
+            let mut search = Search::new(&candidates, need, policy);
+            if let Some(i) = smallest {
+                search.consider(&[i], candidates.sizes[i]);
+            }
+            let complete = search.run();
+            (search.best.map(|(_, picked)| picked), complete)
+        }
+    };
+    let picked = picked?;
+
+    Some(Plan {
+        freed: picked.iter().map(|&i| fs.size(candidates.dirs[i])).sum(),
+        dirs: picked.into_iter().map(|i| candidates.dirs[i]).collect(),
+        complete,
+    })
+}
+
+/// Directories below the root in pre-order, so each subtree occupies the range `i..end[i]`.
+struct Candidates {
+    dirs: Vec<NodeId>,
+    sizes: Vec<usize>,
+    end: Vec<usize>,
+    /// Most that can be freed with non-nested directories from `i..`, which is by taking
+    /// every outermost one.
+    rest: Vec<usize>,
+}
+
+impl Candidates {
+    fn new(fs: &FS) -> Self {
+        let dirs = fs
+            .preorder(fs.root())
+            .skip(1)
+            .filter(|&id| fs.is_dir(id))
+            .collect::<Vec<_>>();
+        let sizes = dirs
+            .iter()
+            .map(|&id| fs.size(id) as usize)
+            .collect::<Vec<_>>();
+        // directories in each subtree, counting its root.
+        let mut counts = vec![0; fs.nodes.len()];
+        for id in fs.postorder(fs.root()).filter(|&id| fs.is_dir(id)) {
+            counts[id.0] = 1 + fs.children(id).map(|c| counts[c.0]).sum::<usize>();
+        }
+        let end = dirs
+            .iter()
+            .enumerate()
+            .map(|(i, &id)| i + counts[id.0])
+            .collect::<Vec<_>>();
+        let mut rest = vec![0; dirs.len() + 1];
+        for i in (0..dirs.len()).rev() {
+            rest[i] = sizes[i] + rest[end[i]];
+        }
+        Self {
+            dirs,
+            sizes,
+            end,
+            rest,
+        }
+    }
+}
+
+/// Branch and bound over sets of non-nested candidates, taking or skipping each one in
+/// pre-order. Gives up after `MAX_STEPS` states, keeping the best set found so far.
+struct Search<'a> {
+    candidates: &'a Candidates,
+    need: usize,
+    policy: Policy,
+    /// The best set so far with its `key`.
+    best: Option<((usize, usize), Vec<usize>)>,
+}
+
+impl<'a> Search<'a> {
+    const MAX_STEPS: usize = 1 << 22;
+
+    fn new(candidates: &'a Candidates, need: usize, policy: Policy) -> Self {
+        Self {
+            candidates,
+            need,
+            policy,
+            best: None,
+        }
+    }
+
+    /// What the policy minimizes, for a set of `count` directories freeing `freed`.
+    fn key(&self, count: usize, freed: usize) -> (usize, usize) {
+        match self.policy {
+            Policy::FewestDirs => (count, freed),
+            _ => (0, freed),
+        }
+    }
+
+    fn beats_best(&self, key: (usize, usize)) -> bool {
+        self.best.as_ref().is_none_or(|(best, _)| key < *best)
+    }
+
+    fn consider(&mut self, picked: &[usize], freed: usize) {
+        let key = self.key(picked.len(), freed);
+        if self.beats_best(key) {
+            self.best = Some((key, picked.to_vec()));
+        }
+    }
+
+    /// Returns whether every state was visited, so that `best` is optimal.
+    fn run(&mut self) -> bool {
+        let Candidates {
+            sizes, end, rest, ..
+        } = self.candidates;
+        let len = sizes.len();
+        let mut picked = vec![];
+        // position, freed so far, how many of `picked` are kept and the candidate taken last.
+        let mut stack = vec![(0, 0, 0, None)];
+        let mut steps = 0;
+        while let Some((i, freed, kept, taken)) = stack.pop() {
+            steps += 1;
+            if steps > Self::MAX_STEPS {
+                return false;
+            }
+            picked.truncate(kept);
+            picked.extend(taken);
+            if freed >= self.need {
+                self.consider(&picked, freed);
+                // nothing frees less than exactly what is needed.
+                if self.policy == Policy::MinimalSet && freed == self.need {
+                    return true;
                 }
+                continue;
+            }
+            // another directory is needed, and it can only add to what is freed.
+            if i == len
+                || freed + rest[i] < self.need
+                || !self.beats_best(self.key(picked.len() + 1, freed))
+            {
+                continue;
             }
+            stack.push((i + 1, freed, picked.len(), None));
+            // taking comes first, which finds a plan to improve on quickly.
+            stack.push((end[i], freed + sizes[i], picked.len(), Some(i)));
         }
+        true
     }
-    Ok(("", fs))
 }
 
 pub fn part_one(input: &str) -> Option<u32> {
-    let fs = parse_input(input).unwrap().1;
-
-    let sum = fs
-        .into_iter()
-        .filter_map(|v| {
-            let size = v.size();
-            if size <= 100000 && v.is_dir() {
-                Some(size)
-            } else {
-                None
-            }
-        })
-        .sum();
-    Some(sum)
+    let fs = parse_input(input).ok()?;
+
+    // post-order fills the size cache bottom-up. Small directories can still be numerous
+    // enough to add up past `u32::MAX`.
+    fs.postorder(fs.root())
+        .filter(|&id| fs.is_dir(id))
+        .map(|id| fs.size(id))
+        .filter(|&size| size <= 100000)
+        .try_fold(0u32, |sum, size| sum.checked_add(size))
 }
 
 pub fn part_two(input: &str) -> Option<u32> {
-    let fs = parse_input(input).unwrap().1;
-    let total = 70000000;
-    let need_space = 30000000;
-    let used = fs.root.size();
-    let free = total - used;
-
-    let need = need_space - free;
-    fs.into_iter()
-        .filter(|v| v.size() >= need && v.is_dir())
-        .map(|v| v.size())
+    let fs = parse_input(input).ok()?;
+    let need = Disk::default().shortfall(fs.size(fs.root()));
+    // unlike the planner, this may pick `/` itself.
+    fs.postorder(fs.root())
+        .filter(|&id| fs.is_dir(id))
+        .map(|id| fs.size(id))
+        .filter(|&size| size >= need)
         .min()
 }
 
+fn ls(fs: &FS, path: &str) {
+    let Some(id) = fs.lookup(path) else {
+        eprintln!("no such file or directory: {}", path);
+        return;
+    };
+    for child in fs.depth_limited(id, 1) {
+        let kind = if fs.is_dir(child) { "dir" } else { "file" };
+        println!(
+            "{:>10} {:<4} {}",
+            fs.size(child),
+            kind,
+            fs.entry(child).name()
+        );
+    }
+}
+
+fn print_plan(fs: &FS, disk: Disk, policy: Policy) {
+    let Some(plan) = plan(fs, disk, policy) else {
+        println!("no way to free enough space.");
+        return;
+    };
+    for &id in &plan.dirs {
+        println!("{:>10} {}", fs.size(id), fs.path(id));
+    }
+    if !plan.complete {
+        println!("stopped searching early, a better plan may exist.");
+    }
+    println!(
+        "freed {} of {} needed",
+        plan.freed,
+        disk.shortfall(fs.size(fs.root()))
+    );
+}
+
+fn from_json(path: &str) -> Result<FS, String> {
+    let json = fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let json: JsonEntry = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+    FS::try_from(&json)
+}
+
+struct Args {
+    ls_path: Option<String>,
+    tree: bool,
+    du: bool,
+    json: bool,
+    policy: Option<Policy>,
+    disk: Disk,
+    from_json: Option<String>,
+    synthesize: Option<u32>,
+    fanout: u32,
+}
+
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        ls_path: args.opt_value_from_str("--ls")?,
+        tree: args.contains("--tree"),
+        du: args.contains("--du"),
+        json: args.contains("--json"),
+        policy: args.opt_value_from_str("--plan")?,
+        disk: Disk {
+            total: args.opt_value_from_str("--disk")?.unwrap_or(70000000),
+            needed: args.opt_value_from_str("--need")?.unwrap_or(30000000),
+        },
+        from_json: args.opt_value_from_str("--from-json")?,
+        synthesize: args.opt_value_from_str("--synthesize")?,
+        fanout: args.opt_value_from_str("--fanout")?.unwrap_or(3),
+    };
+    finish_args(args)?;
+    Ok(parsed)
+}
+
 fn main() {
+    let Args {
+        ls_path,
+        tree,
+        du,
+        json,
+        policy,
+        disk,
+        from_json: from_json_path,
+        synthesize,
+        fanout,
+    } = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!(
+                "usage: `cargo solve 07 -- [--ls path] [--tree] [--du] [--json] \
+                 [--plan smallest|minimal|fewest [--disk N] [--need N]] [--from-json path] \
+                 [--synthesize depth [--fanout N]]`"
+            );
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 7);
+
+    if let Some(path) = from_json_path {
+        match from_json(&path) {
+            Ok(fs) => print!("{}", fs.transcript()),
+            Err(e) => eprintln!("could not read {}: {}", path, e),
+        }
+        return;
+    }
+    if let Some(depth) = synthesize {
+        match FS::synthesize(depth, fanout) {
+            Ok(fs) => print!("{}", fs.transcript()),
+            Err(e) => {
+                eprintln!("could not synthesize: {}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if ls_path.is_some() || tree || du || json || policy.is_some() {
+        match parse_input(input) {
+            Ok(fs) => {
+                if let Some(path) = ls_path {
+                    ls(&fs, &path);
+                }
+                if tree {
+                    print!(
+                        "{}",
+                        Tree {
+                            fs: &fs,
+                            id: fs.root()
+                        }
+                    );
+                }
+                if du {
+                    print!(
+                        "{}",
+                        DiskUsage {
+                            fs: &fs,
+                            id: fs.root()
+                        }
+                    );
+                }
+                if let Some(policy) = policy {
+                    print_plan(&fs, disk, policy);
+                }
+                if json {
+                    // keep stdout pipeable into `--from-json`.
+                    match JsonEntry::try_from(&fs) {
+                        Ok(json) => println!("{}", serde_json::to_string_pretty(&json).unwrap()),
+                        Err(e) => {
+                            eprintln!("could not export: {}", e);
+                            process::exit(1);
+                        }
+                    }
+                    return;
+                }
+            }
+            Err(e) => eprintln!("could not parse input: {}", e),
+        }
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -430,6 +1124,10 @@ fn main() {
 mod tests {
     use super::*;
 
+    fn names(fs: &FS, ids: impl Iterator<Item = NodeId>) -> Vec<&str> {
+        ids.map(|id| fs.entry(id).name()).collect()
+    }
+
     #[test]
     fn test_part_one() {
         let input = advent_of_code::read_file("examples", 7);
@@ -440,5 +1138,394 @@ mod tests {
     fn test_part_two() {
         let input = advent_of_code::read_file("examples", 7);
         assert_eq!(part_two(&input), Some(24933642));
+
+        // only deleting everything frees enough.
+        assert_eq!(part_two("$ ls\n50000000 a\ndir b"), Some(50000000));
+    }
+
+    #[test]
+    fn test_traversals() {
+        let input = advent_of_code::read_file("examples", 7);
+        let fs = parse_input(&input).unwrap();
+        let root = fs.root();
+
+        assert_eq!(
+            names(&fs, fs.preorder(root)),
+            [
+                "/", "a", "e", "i", "f", "g", "h.lst", "b.txt", "c.dat", "d", "d.ext", "d.log",
+                "j", "k"
+            ]
+        );
+        assert_eq!(
+            names(&fs, fs.postorder(root)),
+            [
+                "i", "e", "f", "g", "h.lst", "a", "b.txt", "c.dat", "d.ext", "d.log", "j", "k",
+                "d", "/"
+            ]
+        );
+        assert_eq!(
+            names(&fs, fs.depth_limited(root, 1)),
+            ["/", "a", "b.txt", "c.dat", "d"]
+        );
+        // walking again yields the same nodes.
+        assert_eq!(fs.preorder(root).count(), 14);
+    }
+
+    #[test]
+    fn test_render() {
+        let input = advent_of_code::read_file("examples", 7);
+        let fs = parse_input(&input).unwrap();
+
+        assert_eq!(
+            Tree {
+                fs: &fs,
+                id: fs.root()
+            }
+            .to_string(),
+            "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+"
+        );
+        assert_eq!(
+            DiskUsage {
+                fs: &fs,
+                id: fs.root()
+            }
+            .to_string(),
+            "47M   /\n24M   /d\n93K   /a\n584   /a/e\n"
+        );
+        let a = fs.lookup("/a").unwrap();
+        assert_eq!(
+            Tree { fs: &fs, id: a }.to_string(),
+            "- a (dir)\n  - e (dir)\n    - i (file, size=584)\n  - f (file, size=29116)\n  \
+             - g (file, size=2557)\n  - h.lst (file, size=62596)\n"
+        );
+        assert_eq!(
+            DiskUsage { fs: &fs, id: a }.to_string(),
+            "93K   /a\n584   /a/e\n"
+        );
+        assert_eq!(fs.path(fs.lookup("/a/e").unwrap()), "/a/e");
+    }
+
+    #[test]
+    fn test_parse_names_and_relisting() {
+        let fs = parse_input(
+            "$ cd /tmp/build-2.1
+$ ls
+12 a.out
+dir obj.d
+$ cd ..
+$ ls
+dir build-2.1
+34 notes v2.txt
+$ ls
+dir build-2.1
+34 notes v2.txt
+$ cd build-2.1/obj.d
+$ ls
+5 main.o",
+        )
+        .unwrap();
+
+        assert_eq!(fs.size(fs.root()), 12 + 5 + 34);
+        assert_eq!(fs.size(fs.lookup("/tmp/build-2.1").unwrap()), 17);
+        assert!(fs.lookup("/tmp/notes v2.txt").is_some());
+        assert_eq!(fs.preorder(fs.root()).count(), 7);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_input("$ cd /\n$ rm -rf a").unwrap_err(),
+            ParseError {
+                line: 2,
+                kind: ParseErrorKind::UnknownCommand("$ rm -rf a".into())
+            }
+        );
+        assert_eq!(
+            parse_input("$ cd /\n12 a").unwrap_err().to_string(),
+            "line 2: unexpected output `12 a`"
+        );
+        assert_eq!(
+            parse_input("$ ls\n12 a\nwat").unwrap_err().to_string(),
+            "line 3: unexpected output `wat`"
+        );
+        assert_eq!(
+            parse_input("$ ls\n12 a\n$ cd a").unwrap_err().to_string(),
+            "line 3: `a` is not a directory"
+        );
+        // `import` refuses the same names, so exports always read back in.
+        for line in ["dir a/b", "dir ..", "12 .", "12 a/b"] {
+            assert_eq!(
+                parse_input(&format!("$ ls\n{}", line)).unwrap_err(),
+                ParseError {
+                    line: 2,
+                    kind: ParseErrorKind::UnexpectedOutput(line.into())
+                }
+            );
+        }
+        assert_eq!(
+            parse_input("$ ls\n4000000000 a\n4000000000 a\n300000000 b")
+                .unwrap_err()
+                .to_string(),
+            "line 4: file sizes add up to more than 4294967295"
+        );
+    }
+
+    #[test]
+    fn test_deep_tree() {
+        let chain = "$ cd a\n".repeat(100_000);
+        let input = format!("{}$ ls\n7 f\n", chain);
+        // every directory, `/` too, holds just the one file.
+        assert_eq!(part_one(&input), Some(7 * 100_001));
+        assert_eq!(part_two(&input), Some(7));
+
+        // small directories, but too many of them to add up in a `u32`.
+        let input = format!("{}$ ls\n100000 f\n", chain);
+        assert_eq!(part_one(&input), None);
+
+        // too deep for `--from-json` to read back, so it is not exported at all.
+        let fs = parse_input(&chain).unwrap();
+        assert_eq!(
+            JsonEntry::try_from(&fs).unwrap_err(),
+            "directories are nested more than 62 deep"
+        );
+        for depth in [FS::MAX_JSON_DEPTH, FS::MAX_JSON_DEPTH + 1] {
+            let fs = parse_input(&format!("{}$ ls\n7 f\n", "$ cd a\n".repeat(depth))).unwrap();
+            let json = JsonEntry::try_from(&fs).map(|json| serde_json::to_string(&json).unwrap());
+            assert_eq!(json.is_ok(), depth <= FS::MAX_JSON_DEPTH);
+            if let Ok(json) = json {
+                let imported = FS::try_from(&serde_json::from_str(&json).unwrap()).unwrap();
+                assert_eq!(part_one(&imported.transcript()), part_one(&fs.transcript()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let input = advent_of_code::read_file("examples", 7);
+        let fs = parse_input(&input).unwrap();
+        let json = serde_json::to_string(&JsonEntry::try_from(&fs).unwrap()).unwrap();
+        assert!(
+            json.starts_with(r#"{"type":"dir","name":"/","children":[{"type":"dir","name":"a""#)
+        );
+        assert!(json.contains(r#"{"type":"file","name":"i","size":584}"#));
+
+        let imported = FS::try_from(&serde_json::from_str::<JsonEntry>(&json).unwrap()).unwrap();
+        let reparsed = parse_input(&imported.transcript()).unwrap();
+        assert_eq!(
+            JsonEntry::try_from(&reparsed).unwrap(),
+            JsonEntry::try_from(&fs).unwrap()
+        );
+        assert_eq!(part_one(&imported.transcript()), Some(95437));
+        assert_eq!(part_two(&imported.transcript()), Some(24933642));
+    }
+
+    #[test]
+    fn test_synthesize() {
+        let fs = FS::synthesize(3, 3).unwrap();
+        let transcript = fs.transcript();
+        let parsed = parse_input(&transcript).unwrap();
+        assert_eq!(
+            JsonEntry::try_from(&parsed).unwrap(),
+            JsonEntry::try_from(&fs).unwrap()
+        );
+        assert_eq!(
+            parsed
+                .preorder(parsed.root())
+                .filter(|&id| parsed.is_dir(id))
+                .count(),
+            40
+        );
+
+        // 111110 files, each made small enough for the total to fit.
+        let fs = FS::synthesize(4, 10).unwrap();
+        assert!(fs.size(fs.root()) > 300_000);
+        assert!(FS::synthesize(5, 10).is_err());
+        assert!(FS::synthesize(100, 1).is_err());
+    }
+
+    #[test]
+    fn test_import_errors() {
+        let import = |json: &str| FS::try_from(&serde_json::from_str::<JsonEntry>(json).unwrap());
+        let dir = |children: &[String]| {
+            format!(
+                r#"{{"type":"dir","name":"/","children":[{}]}}"#,
+                children.join(",")
+            )
+        };
+        let file = |name: &str, size: u32| {
+            format!(r#"{{"type":"file","name":{:?},"size":{}}}"#, name, size)
+        };
+
+        assert!(import(&dir(&[file("a", 1)])).is_ok());
+        assert_eq!(
+            import(&dir(&[file("a", 1), file("a", 2)])).unwrap_err(),
+            "duplicate name `a` in /"
+        );
+        let sub = dir(&[file("a", 1)]).replace(r#""name":"/""#, r#""name":"b""#);
+        assert_eq!(
+            import(&dir(&[sub.clone(), sub])).unwrap_err(),
+            "duplicate name `b` in /"
+        );
+        for name in ["", ".", "..", "a/b", "a\nb"] {
+            assert!(import(&dir(&[file(name, 1)])).is_err(), "{:?}", name);
+        }
+        assert_eq!(
+            import(&dir(&[file("a", u32::MAX), file("b", 1)])).unwrap_err(),
+            "file sizes add up to more than 4294967295"
+        );
+        assert!(import(&file("a", 1)).is_err());
+    }
+
+    #[test]
+    fn test_plan() {
+        let fs = parse_input(
+            "$ cd /
+$ ls
+dir a
+dir b
+dir c
+10 x
+$ cd a
+$ ls
+40 y
+dir d
+$ cd d
+$ ls
+25 z
+$ cd /b
+$ ls
+30 w
+$ cd /c
+$ ls
+45 v",
+        )
+        .unwrap();
+        let disk = Disk {
+            total: 200,
+            needed: 105,
+        };
+        let paths = |plan: Plan| {
+            let mut paths = plan.dirs.iter().map(|&id| fs.path(id)).collect::<Vec<_>>();
+            paths.sort();
+            (paths, plan.freed)
+        };
+
+        // used 150, so 55 need to go.
+        assert_eq!(disk.shortfall(fs.size(fs.root())), 55);
+        assert_eq!(
+            paths(plan(&fs, disk, Policy::SmallestDir).unwrap()),
+            (vec!["/a".to_string()], 65)
+        );
+        assert_eq!(
+            paths(plan(&fs, disk, Policy::MinimalSet).unwrap()),
+            (vec!["/a/d".to_string(), "/b".to_string()], 55)
+        );
+        assert_eq!(
+            paths(plan(&fs, disk, Policy::FewestDirs).unwrap()),
+            (vec!["/a".to_string()], 65)
+        );
+
+        let disk = Disk {
+            total: 200,
+            needed: 160,
+        };
+        assert_eq!(
+            paths(plan(&fs, disk, Policy::FewestDirs).unwrap()),
+            (vec!["/a".to_string(), "/c".to_string()], 110)
+        );
+        assert_eq!(plan(&fs, disk, Policy::SmallestDir), None);
+    }
+
+    #[test]
+    fn test_plan_against_brute_force() {
+        for (dirs, seed) in (2..=12).flat_map(|dirs| (1..=10).map(move |seed| (dirs, seed))) {
+            let input = advent_of_code::gen::generate(7, dirs, seed).unwrap();
+            let fs = parse_input(&input).unwrap();
+            let used = fs.size(fs.root());
+            let below_root = fs
+                .preorder(fs.root())
+                .skip(1)
+                .filter(|&id| fs.is_dir(id))
+                .collect::<Vec<_>>();
+            let nested = |a: NodeId, b: NodeId| {
+                std::iter::successors(Some(b), |&p| fs.parent(p)).any(|p| p == a)
+            };
+
+            // (count, freed) of every set of non-nested directories, including none.
+            let sets = (0..1u32 << below_root.len())
+                .map(|mask| {
+                    (0..below_root.len())
+                        .filter(|i| mask >> i & 1 == 1)
+                        .map(|i| below_root[i])
+                        .collect::<Vec<_>>()
+                })
+                .filter(|set| {
+                    set.iter()
+                        .all(|&a| set.iter().all(|&b| a == b || !nested(a, b)))
+                })
+                .map(|set| (set.len(), set.iter().map(|&id| fs.size(id)).sum::<u32>()))
+                .collect::<Vec<_>>();
+
+            let needs = sets.iter().flat_map(|&(_, freed)| [freed, freed + 1]);
+            for need in needs.chain([used / 3, used / 2]) {
+                let disk = Disk {
+                    total: used,
+                    needed: need,
+                };
+                let context = format!("{} on `cargo gen 7 --size {} --seed {}`", need, dirs, seed);
+                let enough = sets.iter().filter(|&&(_, freed)| freed >= need);
+
+                let minimal = plan(&fs, disk, Policy::MinimalSet);
+                assert_eq!(
+                    minimal.as_ref().map(|plan| plan.freed),
+                    enough.clone().map(|&(_, freed)| freed).min(),
+                    "minimal set for {}",
+                    context
+                );
+                let fewest = plan(&fs, disk, Policy::FewestDirs);
+                assert_eq!(
+                    fewest.as_ref().map(|plan| (plan.dirs.len(), plan.freed)),
+                    enough.min().copied(),
+                    "fewest directories for {}",
+                    context
+                );
+                for plan in minimal.iter().chain(&fewest) {
+                    assert!(plan.complete, "{}", context);
+                    let freed = plan.dirs.iter().map(|&id| fs.size(id)).sum::<u32>();
+                    assert_eq!(freed, plan.freed, "{}", context);
+                    let picked = &plan.dirs;
+                    let overlap = picked
+                        .iter()
+                        .any(|&a| picked.iter().any(|&b| a != b && nested(a, b)));
+                    assert!(!overlap, "{}", context);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_lookup() {
+        let input = advent_of_code::read_file("examples", 7);
+        let fs = parse_input(&input).unwrap();
+
+        let e = fs.lookup("/a/e").unwrap();
+        assert_eq!(fs.size(e), 584);
+        assert_eq!(fs.lookup("/"), Some(fs.root()));
+        assert_eq!(fs.size(fs.lookup("/d").unwrap()), 24933642);
+        assert_eq!(fs.lookup("/a/x"), None);
     }
 }
@@ -1,117 +1,208 @@
-use itertools::Itertools;
+use advent_of_code::{helpers::finish_args, ANSI_RESET};
+use std::{fmt::Write, fs, process};
 
-fn parse(input: &str) -> Vec<Vec<u8>> {
-    input
+/// The heights of the trees, or `None` unless the input is rows of digits of equal length.
+fn parse(input: &str) -> Option<Vec<Vec<u8>>> {
+    let grid = input
         .lines()
-        .map(|line| line.chars().map(|v| v as u8 - b'0').collect())
-        .collect()
+        .map(|line| {
+            line.chars()
+                .map(|v| v.to_digit(10).map(|h| h as u8))
+                .collect::<Option<Vec<_>>>()
+        })
+        .collect::<Option<Vec<_>>>()?;
+    let cols = grid.first().map_or(0, Vec::len);
+    grid.iter().all(|row| row.len() == cols).then_some(grid)
 }
 
-pub fn part_one(input: &str) -> Option<u32> {
-    let grid = parse(input);
-    let transposed = transpose(grid.clone());
-    let mut memo = vec![vec![false; grid[0].len()]; grid.len()];
-
-    for (row_idx, row) in grid.iter().enumerate() {
-        for (col_idx, val) in row.iter().enumerate() {
-            if is_edge(&grid, row_idx, col_idx) {
-                memo[row_idx][col_idx] = true;
-                continue;
-            }
-
-            let visible = row[0..col_idx].iter().all(|v| v < val)
-                || row[col_idx + 1..].iter().all(|v| v < val);
+/// What can be seen from and of every tree: whether it is visible from outside the grid and
+/// its scenic score.
+struct Survey {
+    visible: Vec<Vec<bool>>,
+    scores: Vec<Vec<u64>>,
+}
 
-            memo[row_idx][col_idx] |= visible;
+impl Survey {
+    fn new(grid: &[Vec<u8>]) -> Self {
+        let (rows, cols) = (grid.len(), grid.first().map_or(0, Vec::len));
+        let mut survey = Self {
+            visible: vec![vec![false; cols]; rows],
+            scores: vec![vec![1; cols]; rows],
+        };
+        for r in 0..rows {
+            survey.sweep(grid, (0..cols).map(|c| (r, c)));
+            survey.sweep(grid, (0..cols).rev().map(|c| (r, c)));
+        }
+        for c in 0..cols {
+            survey.sweep(grid, (0..rows).map(|r| (r, c)));
+            survey.sweep(grid, (0..rows).rev().map(|r| (r, c)));
         }
+        survey
     }
 
-    for (row_idx, row) in transposed.iter().enumerate() {
-        for (col_idx, val) in row.iter().enumerate() {
-            // if is_edge(&grid, row_idx, col_idx) {
-            //     memo[row_idx][col_idx] = true;
-            //     continue;
-            // }
+    /// Looks along one line of trees from its first tree on, keeping a stack of the trees
+    /// that still block the view, i.e. trees without a taller or equal tree after them.
+    /// Every tree pops the lower ones; whatever is left on top stops its view.
+    fn sweep(&mut self, grid: &[Vec<u8>], line: impl Iterator<Item = (usize, usize)>) {
+        let mut stack: Vec<(usize, u8)> = vec![];
+        for (i, (r, c)) in line.enumerate() {
+            let height = grid[r][c];
+            while stack.last().is_some_and(|&(_, h)| h < height) {
+                stack.pop();
+            }
+            match stack.last() {
+                Some(&(j, _)) => self.scores[r][c] *= (i - j) as u64,
+                None => {
+                    self.visible[r][c] = true;
+                    self.scores[r][c] *= i as u64;
+                }
+            }
+            stack.push((i, height));
+        }
+    }
 
-            let visible = row[0..col_idx].iter().all(|v| v < val)
-                || row[col_idx + 1..].iter().all(|v| v < val);
+    fn best(&self) -> Option<(usize, usize)> {
+        let cols = self.scores.first()?.len();
+        let best = (0..self.scores.len() * cols).max_by_key(|i| self.scores[i / cols][i % cols])?;
+        Some((best / cols, best % cols))
+    }
 
-            // here is only difference
-            memo[col_idx][row_idx] |= visible;
+    /// Scenic scores as a plain PGM (P2) heatmap, scaled so the best tree is white.
+    fn pgm(&self) -> String {
+        let max = self
+            .scores
+            .iter()
+            .flatten()
+            .max()
+            .copied()
+            .unwrap_or(0)
+            .max(1);
+        let mut out = image_header("P2", &self.scores);
+        out.push_str("255\n");
+        for row in &self.scores {
+            let row = row.iter().map(|s| (s * 255 / max).to_string());
+            writeln!(out, "{}", row.collect::<Vec<_>>().join(" ")).unwrap();
         }
+        out
     }
 
-    let result = memo
-        .iter()
-        .map(|v| v.iter().filter(|v| **v).count() as u32)
-        .sum::<u32>();
+    /// Visibility as a plain PPM (P3) image: visible trees green, hidden ones grey, both
+    /// brighter the taller the tree.
+    fn ppm(&self, grid: &[Vec<u8>]) -> String {
+        let mut out = image_header("P3", grid);
+        out.push_str("255\n");
+        for (heights, visible) in grid.iter().zip(&self.visible) {
+            let row = heights.iter().zip(visible).map(|(&h, &v)| {
+                let shade = 60 + h as u32 * 20;
+                if v {
+                    format!("0 {} 0", shade)
+                } else {
+                    format!("{0} {0} {0}", shade / 2)
+                }
+            });
+            writeln!(out, "{}", row.collect::<Vec<_>>().join("  ")).unwrap();
+        }
+        out
+    }
 
-    Some(result)
+    /// The grid for a terminal: heights in green if visible and grey if hidden, on a
+    /// background from black to white by scenic score. The best tree is on red.
+    fn ansi(&self, grid: &[Vec<u8>]) -> String {
+        let max = self
+            .scores
+            .iter()
+            .flatten()
+            .max()
+            .copied()
+            .unwrap_or(0)
+            .max(1);
+        let best = self.best();
+        let mut out = String::new();
+        for (r, heights) in grid.iter().enumerate() {
+            for (c, h) in heights.iter().enumerate() {
+                let fg = if self.visible[r][c] { 82 } else { 244 };
+                // 232 to 255 is the greyscale ramp of the 256 colour palette.
+                let bg = if best == Some((r, c)) {
+                    196
+                } else {
+                    232 + self.scores[r][c] * 23 / max
+                };
+                write!(out, "\x1b[38;5;{}m\x1b[48;5;{}m{}", fg, bg, h).unwrap();
+            }
+            writeln!(out, "{}", ANSI_RESET).unwrap();
+        }
+        out
+    }
 }
 
-fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
-    assert!(!v.is_empty());
-    let len = v[0].len();
-    let mut iters: Vec<_> = v.into_iter().map(|n| n.into_iter()).collect();
-    (0..len)
-        .map(|_| {
-            iters
-                .iter_mut()
-                .map(|n| n.next().unwrap())
-                .collect::<Vec<T>>()
-        })
-        .collect()
+fn image_header<T>(magic: &str, grid: &[Vec<T>]) -> String {
+    let width = grid.first().map_or(0, Vec::len);
+    format!("{}\n{} {}\n", magic, width, grid.len())
 }
 
-fn is_edge(grid: &Vec<Vec<u8>>, row_idx: usize, col_idx: usize) -> bool {
-    row_idx == 0 || col_idx == 0 || row_idx == grid.len() - 1 || col_idx == grid[0].len() - 1
+pub fn part_one(input: &str) -> Option<u32> {
+    let survey = Survey::new(&parse(input)?);
+    Some(survey.visible.iter().flatten().filter(|v| **v).count() as u32)
 }
 
-fn count_iter(iter: impl Iterator<Item = u8>, val: u8) -> u32 {
-    let mut count = 0;
-    for i in iter {
-        count += 1;
-        if i >= val {
-            break;
-        }
-    }
-    count
+pub fn part_two(input: &str) -> Option<u64> {
+    let survey = Survey::new(&parse(input)?);
+    survey.scores.iter().flatten().max().copied()
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let grid = parse(input);
-    let transposed = transpose(grid.clone());
-    let mut memo = vec![vec![1u32; grid[0].len()]; grid.len()];
-
-    for (row_idx, row) in grid.iter().enumerate() {
-        for (col_idx, val) in row.iter().enumerate() {
-            let left = count_iter(row[0..col_idx].iter().copied().rev(), *val);
-            let right = count_iter(row[col_idx + 1..].iter().copied(), *val);
-
-            memo[row_idx][col_idx] *= left * right;
-        }
-    }
-
-    for (row_idx, row) in transposed.iter().enumerate() {
-        for (col_idx, val) in row.iter().enumerate() {
-            let left = count_iter(row[0..col_idx].iter().copied().rev(), *val);
-            let right = count_iter(row[col_idx + 1..].iter().copied(), *val);
-
-            memo[col_idx][row_idx] *= left * right;
-        }
+fn write_image(path: &str, image: String, name: &str) {
+    match fs::write(path, image) {
+        Ok(_) => println!("wrote {} to \"{}\"", name, path),
+        Err(e) => eprintln!("could not write {}: {}", name, e),
     }
+}
 
-    let result = memo
-        .iter()
-        .map(|v| *v.iter().max().unwrap() as u32)
-        .max()
-        .unwrap();
+struct Args {
+    ansi: bool,
+    pgm: Option<String>,
+    ppm: Option<String>,
+}
 
-    Some(result)
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        ansi: args.contains("--ansi"),
+        pgm: args.opt_value_from_str("--pgm")?,
+        ppm: args.opt_value_from_str("--ppm")?,
+    };
+    finish_args(args)?;
+    Ok(parsed)
 }
 
 fn main() {
+    let Args { ansi, pgm, ppm } = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!("usage: `cargo solve 08 -- [--ansi] [--pgm path] [--ppm path]`");
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 8);
+    if ansi || pgm.is_some() || ppm.is_some() {
+        let Some(grid) = parse(input) else {
+            eprintln!("expected rows of digits of equal length");
+            process::exit(1);
+        };
+        let survey = Survey::new(&grid);
+        if ansi {
+            print!("{}", survey.ansi(&grid));
+            if let Some((r, c)) = survey.best() {
+                println!("best tree at row {} column {}", r + 1, c + 1);
+            }
+        }
+        if let Some(path) = pgm {
+            write_image(&path, survey.pgm(), "scenic heatmap");
+        }
+        if let Some(path) = ppm {
+            write_image(&path, survey.ppm(&grid), "visibility map");
+        }
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -119,6 +210,7 @@ fn main() {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use advent_of_code::check::{self, Check};
 
     #[test]
     fn test_part_one() {
@@ -131,4 +223,170 @@ mod tests {
         let input = advent_of_code::read_file("examples", 8);
         assert_eq!(part_two(&input), Some(8));
     }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(parse("30\n25\n"), Some(vec![vec![3, 0], vec![2, 5]]));
+        assert_eq!(parse("3/\n25"), None);
+        assert_eq!(parse("30\n2"), None);
+        assert_eq!(part_one("3é"), None);
+    }
+
+    // the original O(n³) implementations, kept to cross-check the sweeps against.
+    fn reference_part_one(input: &str) -> Option<u32> {
+        let grid = parse(input).unwrap();
+        let transposed = transpose(grid.clone());
+        let mut memo = vec![vec![false; grid[0].len()]; grid.len()];
+
+        for (row_idx, row) in grid.iter().enumerate() {
+            for (col_idx, val) in row.iter().enumerate() {
+                if is_edge(&grid, row_idx, col_idx) {
+                    memo[row_idx][col_idx] = true;
+                    continue;
+                }
+
+                let visible = row[0..col_idx].iter().all(|v| v < val)
+                    || row[col_idx + 1..].iter().all(|v| v < val);
+
+                memo[row_idx][col_idx] |= visible;
+            }
+        }
+
+        for (row_idx, row) in transposed.iter().enumerate() {
+            for (col_idx, val) in row.iter().enumerate() {
+                // if is_edge(&grid, row_idx, col_idx) {
+                //     memo[row_idx][col_idx] = true;
+                //     continue;
+                // }
+
+                let visible = row[0..col_idx].iter().all(|v| v < val)
+                    || row[col_idx + 1..].iter().all(|v| v < val);
+
+                // here is only difference
+                memo[col_idx][row_idx] |= visible;
+            }
+        }
+
+        let result = memo
+            .iter()
+            .map(|v| v.iter().filter(|v| **v).count() as u32)
+            .sum::<u32>();
+
+        Some(result)
+    }
+
+    fn transpose<T>(v: Vec<Vec<T>>) -> Vec<Vec<T>> {
+        assert!(!v.is_empty());
+        let len = v[0].len();
+        let mut iters: Vec<_> = v.into_iter().map(|n| n.into_iter()).collect();
+        (0..len)
+            .map(|_| {
+                iters
+                    .iter_mut()
+                    .map(|n| n.next().unwrap())
+                    .collect::<Vec<T>>()
+            })
+            .collect()
+    }
+
+    fn is_edge(grid: &[Vec<u8>], row_idx: usize, col_idx: usize) -> bool {
+        row_idx == 0 || col_idx == 0 || row_idx == grid.len() - 1 || col_idx == grid[0].len() - 1
+    }
+
+    fn count_iter(iter: impl Iterator<Item = u8>, val: u8) -> u32 {
+        let mut count = 0;
+        for i in iter {
+            count += 1;
+            if i >= val {
+                break;
+            }
+        }
+        count
+    }
+
+    fn reference_part_two(input: &str) -> Option<u64> {
+        let grid = parse(input).unwrap();
+        let transposed = transpose(grid.clone());
+        let mut memo = vec![vec![1u64; grid[0].len()]; grid.len()];
+
+        for (row_idx, row) in grid.iter().enumerate() {
+            for (col_idx, val) in row.iter().enumerate() {
+                let left = count_iter(row[0..col_idx].iter().copied().rev(), *val);
+                let right = count_iter(row[col_idx + 1..].iter().copied(), *val);
+
+                memo[row_idx][col_idx] *= (left * right) as u64;
+            }
+        }
+
+        for (row_idx, row) in transposed.iter().enumerate() {
+            for (col_idx, val) in row.iter().enumerate() {
+                let left = count_iter(row[0..col_idx].iter().copied().rev(), *val);
+                let right = count_iter(row[col_idx + 1..].iter().copied(), *val);
+
+                memo[col_idx][row_idx] *= (left * right) as u64;
+            }
+        }
+
+        let result = memo.iter().map(|v| *v.iter().max().unwrap()).max().unwrap();
+
+        Some(result)
+    }
+
+    #[test]
+    fn test_against_reference() {
+        // the parts answer in different types, so they cannot share a list.
+        let one = [Check::new("part one", part_one, reference_part_one)];
+        let two = [Check::new("part two", part_two, reference_part_two)];
+        check::cross_check(8, 100, 30, &one);
+        check::cross_check(8, 100, 30, &two);
+
+        // generated grids are square, so also try narrow and wide ones.
+        let mut rng = advent_of_code::helpers::Rng::new(8);
+        for _ in 0..200 {
+            let (rows, cols) = (1 + rng.below(12), 1 + rng.below(12));
+            let input = (0..rows)
+                .map(|_| {
+                    (0..cols)
+                        .map(|_| (b'0' + rng.below(10) as u8) as char)
+                        .collect::<String>()
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            one[0].assert_agrees(&input, &input);
+            two[0].assert_agrees(&input, &input);
+        }
+    }
+
+    #[test]
+    fn test_images() {
+        let input = advent_of_code::read_file("examples", 8);
+        let grid = parse(&input).unwrap();
+        let survey = Survey::new(&grid);
+        assert_eq!(survey.best(), Some((3, 2)));
+
+        let pgm = survey.pgm();
+        assert!(pgm.starts_with("P2\n5 5\n255\n0 0 0 0 0\n0 31 127 31 0\n"));
+        assert_eq!(pgm.lines().nth(6), Some("0 31 255 95 0"));
+
+        let ppm = survey.ppm(&grid);
+        assert!(ppm.starts_with("P3\n5 5\n255\n0 120 0  0 60 0  0 120 0  0 200 0  0 120 0\n"));
+        assert_eq!(
+            ppm.lines().nth(4),
+            Some("0 100 0  0 160 0  0 160 0  40 40 40  0 100 0")
+        );
+        assert_eq!(ppm.lines().count(), 3 + 5);
+
+        // the middle tree sees 70 trees in every direction, a score of more than
+        // `u32::MAX / 255`.
+        let mut flat = vec![vec![0; 141]; 141];
+        flat[70][70] = 9;
+        let lone = Survey::new(&flat);
+        assert_eq!(lone.scores[70][70], 70u64.pow(4));
+        let pgm = lone.pgm();
+        assert!(pgm.lines().nth(3 + 70).unwrap().contains(" 255 "));
+
+        let ansi = survey.ansi(&grid);
+        assert_eq!(ansi.lines().count(), 5);
+        assert!(ansi.contains("\x1b[38;5;82m\x1b[48;5;196m5"));
+    }
 }
@@ -1,196 +1,121 @@
+use advent_of_code::helpers::finish_args;
 use itertools::Itertools;
-use std::{cmp::Ordering, collections::HashSet};
-
-#[derive(Debug, PartialEq, Eq, Clone, Default)]
-struct TailHead {
-    tail: Tail,
-    head: Head,
-    memo: HashSet<(i32, i32)>,
-}
-
-enum Relation {
-    Left,
-    Right,
-    Up,
-    Down,
-    UpperLeft,
-    UpperRight,
-    LowerLeft,
-    LowerRight,
-    Same,
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Write},
+    fs, process,
+};
+
+/// A rope of knots in `D` dimensions where knot 0 is the head and every other knot follows
+/// the one before it. Only knots passed to `track` record the positions they visit.
+#[derive(Debug, PartialEq, Eq, Clone)]
+struct Rope<const D: usize> {
+    knots: Vec<Pos<D>>,
+    visited: Vec<Option<HashSet<Pos<D>>>>,
 }
 
-impl TailHead {
-    fn new(tail: Tail, head: Head) -> Self {
+impl<const D: usize> Rope<D> {
+    fn new(len: usize) -> Self {
+        assert!(len > 0, "rope needs at least one knot");
         Self {
-            tail,
-            head,
-            memo: HashSet::new(),
+            knots: vec![Pos::default(); len],
+            visited: vec![None; len],
         }
     }
 
-    fn move_head(&mut self, op: Operation) {
-        assert!(self.is_neighbor());
-
-        let head = self.head.0;
-        let head = match op {
-            Operation::Right => head.move_right(),
-            Operation::Left => head.move_left(),
-            Operation::Up => head.move_up(),
-            Operation::Down => head.move_down(),
-        };
-        self.head.0 = head;
-    }
-
-    fn insert_memo(&mut self) {
-        self.memo.insert((self.tail.0.x, self.tail.0.y));
+    fn track(mut self, knot: usize) -> Self {
+        self.visited[knot] = Some(HashSet::from([self.knots[knot]]));
+        self
     }
 
-    fn count(&self) -> usize {
-        self.memo.len()
+    fn visited(&self, knot: usize) -> Option<&HashSet<Pos<D>>> {
+        self.visited.get(knot)?.as_ref()
     }
 
-    fn move_tail(&mut self) {
-        if self.is_neighbor() {
-            return;
+    fn apply(&mut self, op: &OpeWithDistance) {
+        for _ in 0..op.distance {
+            self.step(op.operation);
         }
-        let relation = self.relation();
-        let tail = self.tail.0;
-        let tail = match relation {
-            Relation::Left => tail.move_left(),
-            Relation::Right => tail.move_right(),
-            Relation::Up => tail.move_up(),
-            Relation::Down => tail.move_down(),
-            Relation::UpperLeft => tail.move_upper_left(),
-            Relation::UpperRight => tail.move_upper_right(),
-            Relation::LowerLeft => tail.move_lower_left(),
-            Relation::LowerRight => tail.move_lower_right(),
-            Relation::Same => todo!(),
-        };
-        self.tail.0 = tail;
-    }
-
-    /// head locates `Relation` of tail
-    /// H
-    ///  T
-    /// returns `Relation::UpperLeft`
-    fn relation(&self) -> Relation {
-        self.head.0.relation(self.tail.0)
     }
 
-    fn is_neighbor(&self) -> bool {
-        self.head.0.is_neighbor(self.tail.0) || self.head.0 == self.tail.0
-    }
-}
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
-struct Pos {
-    x: i32,
-    y: i32,
-}
+    fn step(&mut self, op: Operation) {
+        self.knots[0] = self.knots[0].step(op);
+        self.record(0);
 
-impl Pos {
-    fn move_up(self) -> Self {
-        Self {
-            x: self.x,
-            y: self.y + 1,
+        for i in 1..self.knots.len() {
+            let knot = self.knots[i].follow(self.knots[i - 1]);
+            if knot == self.knots[i] {
+                // knots behind a resting knot do not move either.
+                break;
+            }
+            self.knots[i] = knot;
+            self.record(i);
         }
     }
 
-    fn move_down(self) -> Self {
-        Self {
-            x: self.x,
-            y: self.y - 1,
+    fn record(&mut self, knot: usize) {
+        if let Some(visited) = &mut self.visited[knot] {
+            visited.insert(self.knots[knot]);
         }
     }
+}
 
-    fn move_left(self) -> Self {
-        Self {
-            x: self.x - 1,
-            y: self.y,
-        }
-    }
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+struct Pos<const D: usize>([i32; D]);
 
-    fn move_right(self) -> Self {
-        Self {
-            x: self.x + 1,
-            y: self.y,
-        }
+impl<const D: usize> Default for Pos<D> {
+    fn default() -> Self {
+        Self([0; D])
     }
+}
 
-    fn move_upper_right(self) -> Self {
-        Self {
-            x: self.x + 1,
-            y: self.y + 1,
-        }
+impl Pos<2> {
+    fn new(x: i32, y: i32) -> Self {
+        Self([x, y])
     }
 
-    fn move_upper_left(self) -> Self {
-        Self {
-            x: self.x - 1,
-            y: self.y + 1,
-        }
+    fn x(self) -> i32 {
+        self.0[0]
     }
 
-    fn move_lower_right(self) -> Self {
-        Self {
-            x: self.x + 1,
-            y: self.y - 1,
-        }
+    fn y(self) -> i32 {
+        self.0[1]
     }
+}
 
-    fn move_lower_left(self) -> Self {
-        Self {
-            x: self.x - 1,
-            y: self.y - 1,
-        }
+impl<const D: usize> Pos<D> {
+    fn step(mut self, op: Operation) -> Self {
+        self.0[op.axis] += op.sign;
+        self
     }
 
-    fn relation(self, other: Self) -> Relation {
-        match (self.x.cmp(&other.x), self.y.cmp(&other.y)) {
-            (Ordering::Less, Ordering::Less) => Relation::LowerLeft,
-            (Ordering::Less, Ordering::Equal) => Relation::Left,
-            (Ordering::Less, Ordering::Greater) => Relation::UpperLeft,
-            (Ordering::Equal, Ordering::Less) => Relation::Down,
-            (Ordering::Equal, Ordering::Equal) => Relation::Same,
-            (Ordering::Equal, Ordering::Greater) => Relation::Up,
-            (Ordering::Greater, Ordering::Less) => Relation::LowerRight,
-            (Ordering::Greater, Ordering::Equal) => Relation::Right,
-            (Ordering::Greater, Ordering::Greater) => Relation::UpperRight,
+    /// Where a knot at `self` moves to when the knot ahead of it is at `head`: nowhere while
+    /// they touch, otherwise one step towards `head` along every axis they differ in.
+    fn follow(mut self, head: Self) -> Self {
+        if self.is_touching(head) {
+            return self;
         }
+        for (c, h) in self.0.iter_mut().zip(head.0) {
+            *c += (h - *c).signum();
+        }
+        self
     }
 
-    fn is_neighbor(self, other: Self) -> bool {
-        if self.x == other.x {
-            self.y == other.y + 1 || self.y == other.y - 1
-        } else if self.y == other.y {
-            self.x == other.x + 1 || self.x == other.x - 1
-        } else if self.x + 1 == other.x || self.x - 1 == other.x {
-            self.y + 1 == other.y || self.y - 1 == other.y
-        } else {
-            false
-        }
+    /// Touching includes overlapping and diagonal neighbors across all axes.
+    fn is_touching(self, other: Self) -> bool {
+        self.0.iter().zip(other.0).all(|(a, b)| (a - b).abs() <= 1)
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
-struct Tail(Pos);
-
-#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
-struct Head(Pos);
-
 impl TryFrom<&str> for OpeWithDistance {
     type Error = ();
     fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let (ope, distance) = value.split_whitespace().collect_tuple().unwrap();
-        let distance = distance.parse::<i32>().unwrap();
-        let operation = match ope {
-            "R" => Operation::Right,
-            "L" => Operation::Left,
-            "U" => Operation::Up,
-            "D" => Operation::Down,
-            _ => return Err(()),
-        };
+        let (ope, distance) = value.split_whitespace().collect_tuple().ok_or(())?;
+        let distance = distance.parse::<i32>().map_err(drop)?;
+        if distance < 0 {
+            return Err(());
+        }
+        let operation = ope.parse::<char>().map_err(drop)?.try_into()?;
         Ok(Self {
             operation,
             distance,
@@ -203,67 +128,297 @@ struct OpeWithDistance {
     distance: i32,
 }
 
-#[derive(Debug, Clone, Copy)]
+impl Display for OpeWithDistance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", char::from(self.operation), self.distance)
+    }
+}
+
+/// Letters for the positive and negative direction of each axis: right/left, up/down,
+/// forward/back and ana/kata.
+const AXES: [(char, char); 4] = [('R', 'L'), ('U', 'D'), ('F', 'B'), ('A', 'K')];
 
-enum Operation {
-    Right,
-    Left,
-    Up,
-    Down,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Operation {
+    axis: usize,
+    sign: i32,
 }
 
-pub fn part_one(input: &str) -> Option<i32> {
-    let ops = input
-        .lines()
-        .flat_map(OpeWithDistance::try_from)
-        .collect_vec();
-
-    let mut th = TailHead::default();
-    th.insert_memo();
-    for op in ops {
-        for i in 0..op.distance {
-            th.move_head(op.operation);
-            th.move_tail();
-            th.insert_memo();
+impl TryFrom<char> for Operation {
+    type Error = ();
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        AXES.iter()
+            .enumerate()
+            .find_map(|(axis, &(pos, neg))| match value {
+                _ if value == pos => Some(Self { axis, sign: 1 }),
+                _ if value == neg => Some(Self { axis, sign: -1 }),
+                _ => None,
+            })
+            .ok_or(())
+    }
+}
+
+impl From<Operation> for char {
+    fn from(op: Operation) -> Self {
+        let (pos, neg) = AXES[op.axis];
+        if op.sign > 0 {
+            pos
+        } else {
+            neg
         }
     }
+}
 
-    Some(th.count() as i32)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ParseError {
+    line: usize,
+    kind: ParseErrorKind,
 }
 
-pub fn part_two(input: &str) -> Option<i32> {
-    let ops = input
-        .lines()
-        .flat_map(OpeWithDistance::try_from)
-        .collect_vec();
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParseErrorKind {
+    BadCommand,
+    TooFar,
+}
 
-    let mut ths = (0..9).map(|_| TailHead::default()).collect_vec();
-    ths.iter_mut().for_each(|v| v.insert_memo());
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match self.kind {
+            ParseErrorKind::BadCommand => {
+                write!(f, "expected a direction and a distance like `R 4`")
+            }
+            ParseErrorKind::TooFar => write!(f, "the rope moves too far from the start"),
+        }
+    }
+}
 
-    for op in ops {
-        for _ in 0..op.distance {
-            ths[0].move_head(op.operation);
-            ths[0].move_tail();
-            ths[0].insert_memo();
-            let mut start = 0;
-            let mut end = 1;
-            while end < ths.len() {
-                let tail = ths[start].tail;
-                ths[end].head.0 = tail.0;
-                ths[end].move_tail();
-                ths[end].insert_memo();
-
-                start += 1;
-                end += 1;
+/// Commands that can be applied in `D` dimensions. Commands along other axes are skipped.
+fn parse<const D: usize>(input: &str) -> Result<Vec<OpeWithDistance>, ParseError> {
+    let mut total = 0i32;
+    let mut ops = vec![];
+    for (i, line) in input.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let error = |kind| ParseError { line: i + 1, kind };
+        let op = OpeWithDistance::try_from(line).map_err(|_| error(ParseErrorKind::BadCommand))?;
+        // no knot gets further from the start than the head moves in total, so keeping that
+        // below half the range keeps positions and their differences from overflowing.
+        total = total
+            .checked_add(op.distance)
+            .filter(|&total| total <= i32::MAX / 2)
+            .ok_or(error(ParseErrorKind::TooFar))?;
+        if op.operation.axis < D {
+            ops.push(op);
+        }
+    }
+    Ok(ops)
+}
+
+/// Rope positions after every command plus the full trail of the tail, for rendering.
+struct Recording {
+    frames: Vec<(String, Vec<Pos<2>>)>,
+    trail: Vec<Pos<2>>,
+}
+
+impl Recording {
+    fn new(input: &str, len: usize) -> Result<Self, ParseError> {
+        let mut rope = Rope::<2>::new(len);
+        let mut frames = vec![];
+        let mut trail = vec![Pos::default()];
+        for op in parse::<2>(input)? {
+            for _ in 0..op.distance {
+                rope.step(op.operation);
+                trail.push(*rope.knots.last().unwrap());
             }
+            frames.push((op.to_string(), rope.knots.clone()));
         }
+        Ok(Self { frames, trail })
+    }
+
+    /// Smallest box around every position any knot has been at, as `(min, max)`.
+    fn bounds(&self) -> (Pos<2>, Pos<2>) {
+        // the trail always starts at the origin.
+        let all = self.frames.iter().flat_map(|(_, knots)| knots);
+        let all = all.chain(&self.trail);
+        let (xs, ys): (Vec<_>, Vec<_>) = all.map(|p| (p.x(), p.y())).unzip();
+        (
+            Pos::new(*xs.iter().min().unwrap(), *ys.iter().min().unwrap()),
+            Pos::new(*xs.iter().max().unwrap(), *ys.iter().max().unwrap()),
+        )
+    }
+
+    fn render(&self, cell: impl Fn(Pos<2>) -> char) -> String {
+        let (min, max) = self.bounds();
+        let mut out = String::new();
+        for y in (min.y()..=max.y()).rev() {
+            out.extend((min.x()..=max.x()).map(|x| cell(Pos::new(x, y))));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Frames in the style of the puzzle statement: `H`, then `T` or `1`..`9`, then `s`.
+    fn frames(&self) -> impl Iterator<Item = String> + '_ {
+        self.frames.iter().map(|(command, knots)| {
+            let label = |i: usize| match i {
+                0 => 'H',
+                _ if knots.len() == 2 => 'T',
+                i => char::from_digit(i as u32 % 10, 10).unwrap(),
+            };
+            let grid = self.render(|pos| match knots.iter().position(|&k| k == pos) {
+                Some(i) => label(i),
+                None if pos == Pos::default() => 's',
+                None => '.',
+            });
+            format!("== {} ==\n\n{}", command, grid)
+        })
+    }
+
+    fn trail_map(&self) -> String {
+        let visited = self.trail.iter().collect::<HashSet<_>>();
+        self.render(|pos| match pos {
+            _ if pos == Pos::default() => 's',
+            _ if visited.contains(&pos) => '#',
+            _ => '.',
+        })
+    }
+
+    fn trail_svg(&self) -> String {
+        let (min, max) = self.bounds();
+        let (width, height) = (max.x() - min.x() + 2, max.y() - min.y() + 2);
+        // svg's y axis points down.
+        let points = self
+            .trail
+            .iter()
+            .map(|p| format!("{},{}", p.x(), -p.y()))
+            .join(" ");
+        let end = self.trail.last().unwrap();
+
+        let mut svg = String::new();
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}">"#,
+            min.x() - 1,
+            -max.y() - 1,
+            width,
+            height
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"  <polyline points="{}" fill="none" stroke="black" stroke-width="0.2" stroke-linejoin="round"/>"#,
+            points
+        )
+        .unwrap();
+        writeln!(svg, r#"  <circle cx="0" cy="0" r="0.4" fill="green"/>"#).unwrap();
+        writeln!(
+            svg,
+            r#"  <circle cx="{}" cy="{}" r="0.4" fill="red"/>"#,
+            end.x(),
+            -end.y()
+        )
+        .unwrap();
+        svg.push_str("</svg>\n");
+        svg
     }
+}
+
+/// Number of positions the tail of a `len` knot rope visits in `D` dimensions.
+fn tail_visits<const D: usize>(input: &str, len: usize) -> Result<usize, ParseError> {
+    let mut rope = Rope::<D>::new(len).track(len - 1);
+    parse::<D>(input)?.iter().for_each(|op| rope.apply(op));
+    Ok(rope.visited(len - 1).map_or(0, HashSet::len))
+}
+
+pub fn part_one(input: &str) -> Option<i32> {
+    tail_visits::<2>(input, 2).ok().map(|visits| visits as i32)
+}
+
+pub fn part_two(input: &str) -> Option<i32> {
+    tail_visits::<2>(input, 10).ok().map(|visits| visits as i32)
+}
+
+struct Args {
+    knots: usize,
+    svg_path: Option<String>,
+    dims: Option<usize>,
+    frames: bool,
+}
 
-    Some(ths.iter().last().unwrap().count() as i32)
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        knots: args.opt_value_from_str("--knots")?.unwrap_or(2),
+        svg_path: args.opt_value_from_str("--svg")?,
+        dims: args.opt_value_from_str("--dims")?,
+        frames: args.contains("--frames"),
+    };
+    finish_args(args)?;
+    Ok(parsed)
 }
 
 fn main() {
+    let Args {
+        knots,
+        svg_path,
+        dims,
+        frames,
+    } = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!(
+                "usage: `cargo solve 09 -- [--knots N] [--dims 1|2|3|4] [--frames] [--svg path]`"
+            );
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 9);
+    if knots == 0 {
+        eprintln!("a rope needs at least one knot");
+        process::exit(1);
+    }
+    if let Some(dims) = dims {
+        let visits = match dims {
+            1 => tail_visits::<1>(input, knots),
+            2 => tail_visits::<2>(input, knots),
+            3 => tail_visits::<3>(input, knots),
+            4 => tail_visits::<4>(input, knots),
+            _ => {
+                eprintln!("only 1 to 4 dimensions are supported");
+                process::exit(1);
+            }
+        };
+        match visits {
+            Ok(visits) => println!(
+                "tail of a {} knot rope visits {} positions in {}D",
+                knots, visits, dims
+            ),
+            Err(e) => eprintln!("{}", e),
+        }
+    }
+    if frames || svg_path.is_some() {
+        match Recording::new(input, knots) {
+            Ok(recording) => {
+                if frames {
+                    for frame in recording.frames() {
+                        println!("{}", frame);
+                    }
+                    println!("== tail trail ==\n\n{}", recording.trail_map());
+                }
+                if let Some(path) = svg_path {
+                    match fs::write(&path, recording.trail_svg()) {
+                        Ok(_) => println!("wrote tail trail to \"{}\"", path),
+                        Err(e) => eprintln!("could not write svg: {}", e),
+                    }
+                }
+            }
+            Err(e) => eprintln!("{}", e),
+        }
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -283,4 +438,99 @@ mod tests {
         let input = advent_of_code::read_file("examples", 9);
         assert_eq!(part_two(&input), Some(1));
     }
+
+    #[test]
+    fn test_track_knots() {
+        let input = advent_of_code::read_file("examples", 9);
+        let mut rope = Rope::<2>::new(10).track(1).track(9);
+        parse::<2>(&input)
+            .unwrap()
+            .iter()
+            .for_each(|op| rope.apply(op));
+
+        assert_eq!(rope.visited(1).map(|v| v.len()), Some(13));
+        assert_eq!(rope.visited(9).map(|v| v.len()), Some(1));
+        assert_eq!(rope.visited(5), None);
+        assert_eq!(rope.knots[0], Pos::new(2, 2));
+    }
+
+    #[test]
+    fn test_larger_example() {
+        let input = "R 5\nU 8\nL 8\nD 3\nR 17\nD 10\nL 25\nU 20";
+        assert_eq!(part_two(input), Some(36));
+    }
+
+    #[test]
+    fn test_frames() {
+        let input = advent_of_code::read_file("examples", 9);
+        let recording = Recording::new(&input, 2).unwrap();
+        let frames = recording.frames().collect::<Vec<_>>();
+
+        assert_eq!(frames.len(), 8);
+        assert_eq!(
+            frames[0],
+            "== R 4 ==\n\n......\n......\n......\n......\ns..TH.\n"
+        );
+        assert_eq!(
+            frames[1],
+            "== U 4 ==\n\n....H.\n....T.\n......\n......\ns.....\n"
+        );
+        assert_eq!(
+            recording.trail_map(),
+            "..##..\n...##.\n.####.\n....#.\ns###..\n"
+        );
+
+        let frame = Recording::new(&input, 10).unwrap().frames().nth(1).unwrap();
+        assert_eq!(
+            frame,
+            "== U 4 ==\n\n....H.\n....1.\n..432.\n.5....\n6.....\n"
+        );
+    }
+
+    #[test]
+    fn test_trail_svg() {
+        let recording = Recording::new("R 2\nU 2", 2).unwrap();
+        let svg = recording.trail_svg();
+        assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="-1 -3 4 4">"#));
+        assert!(svg.contains(r#"points="0,0 0,0 1,0 1,0 2,-1""#));
+    }
+
+    #[test]
+    fn test_dimensions() {
+        let input = advent_of_code::read_file("examples", 9);
+        // a 2D path behaves the same when embedded in more dimensions.
+        assert_eq!(tail_visits::<3>(&input, 2), Ok(13));
+        assert_eq!(tail_visits::<4>(&input, 10), Ok(1));
+        // commands along axes that do not exist are skipped.
+        assert_eq!(tail_visits::<1>("R 3\nU 5\nL 1", 2), Ok(3));
+
+        // the tail only moves once the head is two steps away along any axis.
+        assert_eq!(tail_visits::<3>("F 2\nU 1\nR 1\nR 1", 2), Ok(3));
+        let mut rope = Rope::<4>::new(2);
+        parse::<4>("A 1\nF 1\nU 1\nR 1\nA 1")
+            .unwrap()
+            .iter()
+            .for_each(|op| rope.apply(op));
+        assert_eq!(rope.knots, [Pos([1, 1, 1, 2]), Pos([1, 1, 1, 1])]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |line, kind| Err(ParseError { line, kind });
+        assert_eq!(
+            tail_visits::<2>("R 1\nX 2", 2),
+            error(2, ParseErrorKind::BadCommand)
+        );
+        assert_eq!(
+            tail_visits::<2>("R 1\n\nL", 2),
+            error(3, ParseErrorKind::BadCommand)
+        );
+        assert_eq!(
+            tail_visits::<2>("U -4", 2),
+            error(1, ParseErrorKind::BadCommand)
+        );
+        let far = format!("R {0}\nL {0}", i32::MAX / 2);
+        assert_eq!(tail_visits::<2>(&far, 2), error(2, ParseErrorKind::TooFar));
+        assert_eq!(part_one("R 4 4"), None);
+    }
 }
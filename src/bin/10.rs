@@ -1,4 +1,11 @@
-#[derive(Debug, Clone, Copy)]
+use advent_of_code::helpers::finish_args;
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    process,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Op {
     Noop,
     Addx(i32),
@@ -13,8 +20,14 @@ impl Op {
     }
 }
 
-// impl<U> TryFrom<U> for Op
-// where U: Into<Op> {}
+impl Display for Op {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Op::Noop => write!(f, "noop"),
+            Op::Addx(x) => write!(f, "addx {}", x),
+        }
+    }
+}
 
 impl TryFrom<&str> for Op {
     type Error = &'static str;
@@ -22,28 +35,99 @@ impl TryFrom<&str> for Op {
     fn try_from(value: &str) -> Result<Self, Self::Error> {
         let mut parts = value.split_whitespace();
         let op = parts.next().ok_or("No op")?;
-        match op {
-            "noop" => Ok(Op::Noop),
-            "addx" => Ok(Op::Addx(
+        let op = match op {
+            "noop" => Op::Noop,
+            "addx" => Op::Addx(
                 parts
                     .next()
                     .ok_or("No arg")?
                     .parse()
                     .map_err(|_| "Invalid")?,
-            )),
-            _ => Err("Invalid op"),
+            ),
+            _ => return Err("Invalid op"),
+        };
+        match parts.next() {
+            Some(_) => Err("Too many args"),
+            None => Ok(op),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Error {
+    /// Line of the program and what is wrong with it.
+    Parse(usize, &'static str),
+    /// Program counter of an `addx` that overflows the register.
+    Overflow(usize),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Parse(line, reason) => write!(f, "line {}: {}", line, reason),
+            Error::Overflow(pc) => write!(f, "pc {}: X overflows", pc),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Program(Vec<Op>);
+
+impl TryFrom<&str> for Program {
+    type Error = Error;
+
+    fn try_from(input: &str) -> Result<Self, Self::Error> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| Op::try_from(line).map_err(|reason| Error::Parse(i + 1, reason)))
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+
+/// Disassembly listing: program counter, the cycle the op starts on and the op itself.
+impl Display for Program {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut cycle = 1;
+        for (pc, op) in self.0.iter().enumerate() {
+            writeln!(f, "{:04}  @{:<4}  {}", pc, cycle, op)?;
+            cycle += op.cycle();
         }
+        Ok(())
+    }
+}
+
+/// State of the cpu *during* a cycle, i.e. before the current op has completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Tick {
+    cycle: u32,
+    pc: usize,
+    op: Op,
+    x: i32,
+}
+
+trait Observer {
+    fn on_cycle(&mut self, tick: &Tick);
+}
+
+impl<O: Observer + ?Sized> Observer for &mut O {
+    fn on_cycle(&mut self, tick: &Tick) {
+        (**self).on_cycle(tick);
+    }
+}
+
+impl<A: Observer, B: Observer> Observer for (A, B) {
+    fn on_cycle(&mut self, tick: &Tick) {
+        self.0.on_cycle(tick);
+        self.1.on_cycle(tick);
     }
 }
 
 struct Cpu {
-    // part1
     register_x: i32,
     cycle: u32,
-    state: Vec<i32>,
-
-    // part2
-    display: [[bool; 40]; 6],
 }
 
 impl Cpu {
@@ -51,82 +135,168 @@ impl Cpu {
         Cpu {
             register_x: 1,
             cycle: 0,
-            state: Vec::new(),
+        }
+    }
 
-            display: [[false; 40]; 6],
+    fn run(&mut self, program: &Program, observer: &mut impl Observer) -> Result<(), Error> {
+        for (pc, op) in program.0.iter().enumerate() {
+            self.step(pc, *op, observer)?;
         }
+        Ok(())
     }
 
-    fn process(&mut self, op: Op) {
-        self.cycle += op.cycle();
+    fn step(&mut self, pc: usize, op: Op, observer: &mut impl Observer) -> Result<(), Error> {
         for _ in 0..op.cycle() {
-            self.state.push(self.register_x);
+            self.cycle += 1;
+            observer.on_cycle(&Tick {
+                cycle: self.cycle,
+                pc,
+                op,
+                x: self.register_x,
+            });
         }
-        match op {
-            Op::Noop => {}
-            Op::Addx(x) => self.register_x += x,
+        if let Op::Addx(x) = op {
+            self.register_x = self.register_x.checked_add(x).ok_or(Error::Overflow(pc))?;
         }
+        Ok(())
     }
+}
 
-    fn calc_score(&self, cycle: u32) -> i32 {
-        cycle as i32 * self.state[cycle as usize - 1]
-    }
+#[derive(Debug, Default)]
+struct SignalStrength {
+    sum: i64,
+}
 
-    fn process2(&mut self, op: Op) {
-        let cycle = op.cycle();
-        for _ in 0..cycle {
-            self.draw();
-            self.cycle += 1;
+impl Observer for SignalStrength {
+    fn on_cycle(&mut self, tick: &Tick) {
+        if tick.cycle <= 220 && tick.cycle % 40 == 20 {
+            self.sum += tick.cycle as i64 * tick.x as i64;
         }
-        match op {
-            Op::Noop => {}
-            Op::Addx(x) => self.register_x += x,
+    }
+}
+
+struct Crt {
+    display: [[bool; 40]; 6],
+}
+
+impl Crt {
+    fn new() -> Self {
+        Crt {
+            display: [[false; 40]; 6],
         }
     }
+}
 
-    fn draw(&mut self) {
-        let (x, y) = self.pos();
-        if self.register_x - 1 <= x as i32 && x as i32 <= self.register_x + 1 {
+impl Observer for Crt {
+    fn on_cycle(&mut self, tick: &Tick) {
+        let pos = tick.cycle as usize - 1;
+        let (x, y) = (pos % 40, pos / 40);
+        if y < self.display.len() && tick.x.abs_diff(x as i32) <= 1 {
             self.display[y][x] = true;
         }
     }
+}
 
-    fn pos(&self) -> (usize, usize) {
-        ((self.cycle % 40) as usize, (self.cycle / 40) as usize)
+impl Display for Crt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for row in self.display {
+            for col in row {
+                write!(f, "{}", if col { '#' } else { '.' })?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
     }
 }
 
-pub fn part_one(input: &str) -> Option<i32> {
-    let mut cpu = Cpu::new();
-    let programs = input.lines().flat_map(Op::try_from);
-    programs.for_each(|p| cpu.process(p));
+struct Tracer<W: Write> {
+    out: W,
+}
 
-    let start_cycle = 20;
-    Some(
-        (start_cycle..)
-            .step_by(40)
-            .take(6)
-            .map(|i| cpu.calc_score(i))
-            .sum(),
-    )
+impl<W: Write> Observer for Tracer<W> {
+    fn on_cycle(&mut self, tick: &Tick) {
+        writeln!(
+            self.out,
+            "cycle {:>4} | pc {:>4} | {:<10} | X = {}",
+            tick.cycle,
+            tick.pc,
+            tick.op.to_string(),
+            tick.x
+        )
+        .expect("could not write trace");
+    }
 }
 
-pub fn part_two(input: &str) -> Option<u32> {
-    let mut cpu = Cpu::new();
-    let programs = input.lines().flat_map(Op::try_from);
-    programs.for_each(|p| cpu.process2(p));
+fn trace(program: &Program) -> Result<(), Error> {
+    let mut tracer = Tracer { out: io::stdout() };
+    let mut signal = SignalStrength::default();
+    let mut crt = Crt::new();
+    Cpu::new().run(program, &mut (&mut tracer, (&mut signal, &mut crt)))?;
 
-    for row in cpu.display {
-        for col in row {
-            print!("{}", if col { '#' } else { '.' });
-        }
-        println!();
-    }
+    println!("signal strength: {}", signal.sum);
+    print!("{}", crt);
+    Ok(())
+}
+
+pub fn part_one(input: &str) -> Option<i64> {
+    let program = Program::try_from(input).ok()?;
+    let mut signal = SignalStrength::default();
+    Cpu::new().run(&program, &mut signal).ok()?;
+    Some(signal.sum)
+}
+
+/// The screen drawn by the program, without printing it.
+pub fn screen(input: &str) -> Option<String> {
+    let program = Program::try_from(input).ok()?;
+    let mut crt = Crt::new();
+    Cpu::new().run(&program, &mut crt).ok()?;
+    Some(crt.to_string())
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    print!("{}", screen(input)?);
     None
 }
 
+struct Args {
+    disassemble: bool,
+    trace: bool,
+}
+
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        disassemble: args.contains("--disassemble"),
+        trace: args.contains("--trace"),
+    };
+    finish_args(args)?;
+    Ok(parsed)
+}
+
 fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!("usage: `cargo solve 10 -- [--disassemble] [--trace]`");
+            process::exit(1);
+        }
+    };
     let input = &advent_of_code::read_file("inputs", 10);
+    if args.disassemble || args.trace {
+        let result = Program::try_from(input.as_str()).and_then(|program| {
+            if args.disassemble {
+                print!("{}", program);
+            }
+            if args.trace {
+                trace(&program)?;
+            }
+            Ok(())
+        });
+        if let Err(e) = result {
+            eprintln!("{}", e);
+        }
+    }
     advent_of_code::solve!(1, part_one, input);
     advent_of_code::solve!(2, part_two, input);
 }
@@ -146,4 +316,56 @@ mod tests {
         let input = advent_of_code::read_file("examples", 10);
         assert_eq!(part_two(&input), None);
     }
+
+    #[test]
+    fn test_crt() {
+        let input = advent_of_code::read_file("examples", 10);
+        let mut crt = Crt::new();
+        let program = Program::try_from(input.as_str()).unwrap();
+        Cpu::new().run(&program, &mut crt).unwrap();
+        assert_eq!(
+            crt.to_string(),
+            "##..##..##..##..##..##..##..##..##..##..\n\
+             ###...###...###...###...###...###...###.\n\
+             ####....####....####....####....####....\n\
+             #####.....#####.....#####.....#####.....\n\
+             ######......######......######......####\n\
+             #######.......#######.......#######.....\n"
+        );
+    }
+
+    #[test]
+    fn test_trace() {
+        let program = Program::try_from("noop\naddx 3\naddx -5").unwrap();
+        assert_eq!(
+            program.to_string(),
+            "0000  @1     noop\n0001  @2     addx 3\n0002  @4     addx -5\n"
+        );
+
+        let mut tracer = Tracer { out: Vec::new() };
+        Cpu::new().run(&program, &mut tracer).unwrap();
+        let trace = String::from_utf8(tracer.out).unwrap();
+        assert_eq!(trace.lines().count(), 5);
+        assert_eq!(
+            trace.lines().last(),
+            Some("cycle    5 | pc    2 | addx -5    | X = 4")
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Program::try_from("noop\n\naddx"),
+            Err(Error::Parse(3, "No arg"))
+        );
+        assert_eq!(
+            Program::try_from("addx 1 2").unwrap_err().to_string(),
+            "line 1: Too many args"
+        );
+        let program = Program::try_from("addx 2147483646\nnoop\naddx 1").unwrap();
+        let mut signal = SignalStrength::default();
+        let result = Cpu::new().run(&program, &mut signal);
+        assert_eq!(result, Err(Error::Overflow(2)));
+        assert_eq!(part_one("addx -2147483647\naddx -2147483647"), None);
+    }
 }
@@ -0,0 +1,63 @@
+use advent_of_code::{gen, helpers::finish_args};
+use std::{fs, process};
+
+struct Args {
+    day: u8,
+    size: usize,
+    seed: u64,
+    out: Option<String>,
+}
+
+fn parse_args() -> Result<Args, pico_args::Error> {
+    let mut args = pico_args::Arguments::from_env();
+    let parsed = Args {
+        size: args.opt_value_from_str("--size")?.unwrap_or(1000),
+        seed: args.opt_value_from_str("--seed")?.unwrap_or(1),
+        out: args.opt_value_from_str("--out")?,
+        day: args.free_from_str()?,
+    };
+    finish_args(args)?;
+    Ok(parsed)
+}
+
+fn main() {
+    let args = match parse_args() {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("Failed to process arguments: {}", e);
+            eprintln!("usage: `cargo gen <day> [--size N] [--seed S] [--out path]`");
+            process::exit(1);
+        }
+    };
+
+    let input = match gen::generate(args.day, args.size, args.seed) {
+        Some(input) => input,
+        None => {
+            eprintln!("No generator for day {}. Generators exist for:", args.day);
+            for (day, size) in gen::DAYS {
+                eprintln!("  day {:>2}: size counts {}", day, size);
+            }
+            process::exit(1);
+        }
+    };
+
+    let path = args
+        .out
+        .unwrap_or_else(|| format!("src/generated/{:02}.txt", args.day));
+    if let Some(dir) = std::path::Path::new(&path).parent() {
+        if let Err(e) = fs::create_dir_all(dir) {
+            eprintln!("Failed to create directory \"{}\": {}", dir.display(), e);
+            process::exit(1);
+        }
+    }
+    match fs::write(&path, input) {
+        Ok(_) => println!(
+            "🎄 Wrote day {} input of size {} (seed {}) to \"{}\"",
+            args.day, args.size, args.seed, path
+        ),
+        Err(e) => {
+            eprintln!("Failed to write input: {}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,80 @@
+/*
+ * Cross-checks optimized solutions against slow but obviously correct reference
+ * implementations, on generated inputs. Register the pairs of a day in its tests:
+ *
+ *     check::cross_check(6, 100, 500, &[
+ *         Check::new("part one", part_one, reference_part_one),
+ *         Check::new("part two", part_two, reference_part_two),
+ *     ]);
+ */
+use crate::gen;
+use std::fmt::{Debug, Display};
+
+/// An optimized implementation together with the reference it has to agree with.
+pub struct Check<T> {
+    name: &'static str,
+    solution: fn(&str) -> T,
+    reference: fn(&str) -> T,
+}
+
+impl<T: PartialEq + Debug> Check<T> {
+    pub fn new(name: &'static str, solution: fn(&str) -> T, reference: fn(&str) -> T) -> Self {
+        Self {
+            name,
+            solution,
+            reference,
+        }
+    }
+
+    /// Panics if solution and reference disagree on `input`, naming where it came from.
+    pub fn assert_agrees(&self, input: &str, origin: impl Display) {
+        let (got, expected) = ((self.solution)(input), (self.reference)(input));
+        assert!(
+            got == expected,
+            "{} gave {:?} but its reference gave {:?} on {}",
+            self.name,
+            got,
+            expected,
+            origin
+        );
+    }
+}
+
+/// Runs every check on `runs` generated inputs for `day`, of sizes from 1 to `max_size`.
+/// A failure prints the `cargo gen` command that reproduces its input.
+pub fn cross_check<T: PartialEq + Debug>(day: u8, runs: u64, max_size: usize, checks: &[Check<T>]) {
+    assert!(max_size > 0, "sizes start at 1");
+    for seed in 0..runs {
+        let size = 1 + seed as usize % max_size;
+        let input = gen::generate(day, size, seed)
+            .unwrap_or_else(|| panic!("no generator for day {}", day));
+        for check in checks {
+            let origin = format!("`cargo gen {} --size {} --seed {}`", day, size, seed);
+            check.assert_agrees(&input, origin);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &str) -> usize {
+        input.lines().count()
+    }
+
+    fn newlines(input: &str) -> usize {
+        input.matches('\n').count()
+    }
+
+    #[test]
+    fn test_cross_check() {
+        cross_check(8, 20, 10, &[Check::new("lines", lines, newlines)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "lines gave 1 but its reference gave 0 on \"x\"")]
+    fn test_disagreement() {
+        Check::new("lines", lines, newlines).assert_agrees("x", "\"x\"");
+    }
+}
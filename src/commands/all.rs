@@ -0,0 +1,63 @@
+use super::{
+    solve::{self, Report},
+    Day, Error, Format, Globals,
+};
+use advent_of_code::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
+use serde::Serialize;
+use std::path::Path;
+
+#[derive(Debug, Serialize)]
+struct Summary {
+    days: Vec<Report>,
+    total_ms: f64,
+}
+
+pub fn run(globals: &Globals) -> Result<(), Error> {
+    let format = globals.format();
+    let mut days = vec![];
+    let mut total = 0_f64;
+    for day in (1..=25).filter_map(Day::new) {
+        // a day that does not exist or fails counts as not solved.
+        let output = Some(day)
+            .filter(|day| Path::new(&format!("src/bin/{}.rs", day)).exists())
+            .and_then(|day| solve::capture(day, globals.release, &[]).ok())
+            .unwrap_or_default();
+        let is_empty = output.is_empty();
+        if !is_empty {
+            total += advent_of_code::parse_exec_time(&output);
+        }
+
+        match format {
+            Format::Text => {
+                println!("----------");
+                println!("{}| Day {} |{}", ANSI_BOLD, day, ANSI_RESET);
+                println!("----------");
+                println!(
+                    "{}",
+                    if is_empty {
+                        "Not solved."
+                    } else {
+                        output.trim()
+                    }
+                );
+            }
+            Format::Json if !is_empty => days.push(solve::report_from(day, &output)),
+            Format::Json => {}
+        }
+    }
+
+    match format {
+        Format::Text => println!(
+            "{}Total:{} {}{:.2}ms{}",
+            ANSI_BOLD, ANSI_RESET, ANSI_ITALIC, total, ANSI_RESET
+        ),
+        Format::Json => {
+            let summary = Summary {
+                days,
+                total_ms: total,
+            };
+            println!("{}", serde_json::to_string_pretty(&summary).unwrap());
+        }
+    }
+    Ok(())
+}
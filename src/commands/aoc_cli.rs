@@ -0,0 +1,30 @@
+/*
+ * Wrapper around aoc-cli (https://github.com/scarvalhojr/aoc-cli), which does the talking to
+ * the Advent of Code website.
+ */
+use super::{Day, Error};
+use std::process::Command;
+
+/// Runs `aoc <args>` for `day` with the terminal attached, describing it as `action`.
+pub fn run(action: &str, day: Day, year: Option<u16>, args: &[String]) -> Result<(), Error> {
+    // check if aoc binary exists and is callable.
+    if Command::new("aoc").arg("-V").output().is_err() {
+        return Err(Error::Failed(
+            "command \"aoc\" not found or not callable. Try running \"cargo install aoc-cli\" to install it.".into(),
+        ));
+    }
+
+    let mut cmd_args = vec![];
+    if let Some(year) = year {
+        cmd_args.push("--year".into());
+        cmd_args.push(year.to_string());
+    }
+    cmd_args.push("--day".into());
+    cmd_args.push(day.number().to_string());
+    cmd_args.extend_from_slice(args);
+
+    println!("{} with >aoc {}", action, cmd_args.join(" "));
+    let mut cmd = Command::new("aoc");
+    cmd.args(cmd_args);
+    super::run(cmd)
+}
@@ -0,0 +1,22 @@
+use super::{cargo, Day, Error};
+
+pub enum Baseline {
+    Save(String),
+    Compare(String),
+}
+
+/// Runs the criterion suite in `benches/days.rs`, for a single day if given.
+pub fn run(day: Option<Day>, baseline: Option<Baseline>) -> Result<(), Error> {
+    let mut cmd = cargo("bench", false);
+    cmd.args(["--bench", "days", "--"]);
+    match baseline {
+        Some(Baseline::Save(name)) => cmd.args(["--save-baseline", &name]),
+        Some(Baseline::Compare(name)) => cmd.args(["--baseline", &name]),
+        None => &mut cmd,
+    };
+    if let Some(day) = day {
+        // benchmark groups are named `day01` and so on.
+        cmd.arg(format!("day{}", day));
+    }
+    super::run(cmd)
+}
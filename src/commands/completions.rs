@@ -0,0 +1,263 @@
+/*
+ * Shell completion scripts, generated from `COMMANDS`. Values written like `<a|b>`, for flags
+ * or for the first argument of a command, are completed from their choices.
+ */
+use super::{Command, Error, Flag, COMMANDS, GLOBAL_FLAGS};
+use std::{fmt::Write, str::FromStr};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            _ => Err(format!(
+                "unsupported shell `{}`, expected bash, zsh or fish",
+                s
+            )),
+        }
+    }
+}
+
+const HELP: Flag = Flag::new("--help", None, "print help");
+
+/// `a b` for a value written as `<a|b>`.
+fn choices(value: &str) -> Option<Vec<&str>> {
+    let inner = value.strip_prefix('<')?.strip_suffix('>')?;
+    inner.contains('|').then(|| inner.split('|').collect())
+}
+
+/// Choices for the first argument of a command.
+fn arg_choices(command: &Command) -> Option<Vec<&'static str>> {
+    choices(command.args.split_whitespace().next()?)
+}
+
+fn flags(command: &Command) -> impl Iterator<Item = &'static Flag> + '_ {
+    command
+        .flags
+        .iter()
+        .chain(command.global_flags())
+        .chain([&HELP])
+}
+
+pub fn generate(shell: Shell, name: &str) -> String {
+    match shell {
+        Shell::Bash => bash(name),
+        Shell::Zsh => zsh(name),
+        Shell::Fish => fish(name),
+    }
+}
+
+fn bash(name: &str) -> String {
+    let function = format!("_{}", name.replace(|c: char| !c.is_alphanumeric(), "_"));
+    let names = COMMANDS.iter().map(|c| c.name).collect::<Vec<_>>();
+    let mut out = String::new();
+    writeln!(out, "{}() {{", function).unwrap();
+    out.push_str("    local cur prev command i\n");
+    out.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+
+    // values of flags.
+    out.push_str("    case \"$prev\" in\n");
+    let all_flags = GLOBAL_FLAGS
+        .iter()
+        .chain(COMMANDS.iter().flat_map(|c| c.flags));
+    for flag in all_flags {
+        let Some(value) = flag.value else {
+            continue;
+        };
+        let words = choices(value).map(|c| c.join(" ")).unwrap_or_default();
+        writeln!(
+            out,
+            "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;",
+            flag.name, words
+        )
+        .unwrap();
+    }
+    out.push_str("    esac\n");
+
+    writeln!(out, "    for ((i = 1; i < COMP_CWORD; i++)); do").unwrap();
+    writeln!(out, "        case \"${{COMP_WORDS[i]}}\" in").unwrap();
+    writeln!(
+        out,
+        "            {}) command=\"${{COMP_WORDS[i]}}\"; break ;;",
+        names.join("|")
+    )
+    .unwrap();
+    out.push_str("        esac\n    done\n\n");
+
+    out.push_str("    case \"$command\" in\n");
+    let globals = GLOBAL_FLAGS.iter().chain([&HELP]).map(|f| f.name);
+    let words = names.iter().copied().chain(globals).collect::<Vec<_>>();
+    writeln!(
+        out,
+        "        \"\") COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;",
+        words.join(" ")
+    )
+    .unwrap();
+    for command in &COMMANDS {
+        let args = arg_choices(command).unwrap_or_default();
+        let words = args
+            .into_iter()
+            .chain(flags(command).map(|f| f.name))
+            .collect::<Vec<_>>();
+        writeln!(
+            out,
+            "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;",
+            command.name,
+            words.join(" ")
+        )
+        .unwrap();
+    }
+    out.push_str("    esac\n}\n\n");
+    writeln!(out, "complete -F {} {}", function, name).unwrap();
+    out
+}
+
+fn zsh_escape(s: &str) -> String {
+    s.replace('\'', "'\\''")
+        .replace(':', "\\:")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+}
+
+fn zsh_flag(flag: &Flag) -> String {
+    let value = match flag.value {
+        Some(value) => match choices(value) {
+            Some(choices) => format!(":{}:({})", &value[1..value.len() - 1], choices.join(" ")),
+            None => format!(":{}:", &value[1..value.len() - 1]),
+        },
+        None => String::new(),
+    };
+    format!("'{}[{}]{}'", flag.name, zsh_escape(flag.about), value)
+}
+
+fn zsh(name: &str) -> String {
+    let function = format!("_{}", name.replace(|c: char| !c.is_alphanumeric(), "_"));
+    let mut out = format!("#compdef {}\n\n{}() {{\n", name, function);
+    out.push_str("    local line state\n\n    _arguments -C \\\n");
+    for flag in GLOBAL_FLAGS.iter().chain([&HELP]) {
+        writeln!(out, "        {} \\", zsh_flag(flag)).unwrap();
+    }
+    out.push_str("        '1:command:->command' \\\n        '*::arg:->args'\n\n");
+
+    out.push_str("    case $state in\n        command)\n            local -a commands\n");
+    out.push_str("            commands=(\n");
+    for command in &COMMANDS {
+        writeln!(
+            out,
+            "                '{}:{}'",
+            command.name,
+            zsh_escape(command.about)
+        )
+        .unwrap();
+    }
+    out.push_str("            )\n            _describe 'command' commands\n            ;;\n");
+
+    out.push_str("        args)\n            case $line[1] in\n");
+    for command in &COMMANDS {
+        let mut specs = flags(command).map(zsh_flag).collect::<Vec<_>>();
+        if let Some(choices) = arg_choices(command) {
+            specs.push(format!("'1:value:({})'", choices.join(" ")));
+        }
+        writeln!(
+            out,
+            "                {}) _arguments {} ;;",
+            command.name,
+            specs.join(" ")
+        )
+        .unwrap();
+    }
+    out.push_str("            esac\n            ;;\n    esac\n}\n\n");
+    writeln!(out, "{} \"$@\"", function).unwrap();
+    out
+}
+
+fn fish_flag(name: &str, condition: &str, flag: &Flag) -> String {
+    let mut line = format!(
+        "complete -c {} -n '{}' -l {}",
+        name,
+        condition,
+        &flag.name[2..]
+    );
+    if let Some(value) = flag.value {
+        match choices(value) {
+            Some(choices) => write!(line, " -xa '{}'", choices.join(" ")).unwrap(),
+            None => line.push_str(" -x"),
+        }
+    }
+    write!(line, " -d '{}'", flag.about.replace('\'', "\\'")).unwrap();
+    line
+}
+
+fn fish(name: &str) -> String {
+    let mut out = format!("complete -c {} -f\n", name);
+    for flag in GLOBAL_FLAGS.iter().chain([&HELP]) {
+        writeln!(out, "{}", fish_flag(name, "__fish_use_subcommand", flag)).unwrap();
+    }
+    for command in &COMMANDS {
+        writeln!(
+            out,
+            "complete -c {} -n '__fish_use_subcommand' -a {} -d '{}'",
+            name,
+            command.name,
+            command.about.replace('\'', "\\'")
+        )
+        .unwrap();
+    }
+    for command in &COMMANDS {
+        let condition = format!("__fish_seen_subcommand_from {}", command.name);
+        for flag in flags(command) {
+            writeln!(out, "{}", fish_flag(name, &condition, flag)).unwrap();
+        }
+        if let Some(choices) = arg_choices(command) {
+            writeln!(
+                out,
+                "complete -c {} -n '{}' -a '{}'",
+                name,
+                condition,
+                choices.join(" ")
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+pub fn run(shell: Shell, name: &str) -> Result<(), Error> {
+    print!("{}", generate(shell, name));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completions() {
+        let bash = generate(Shell::Bash, "aoc");
+        assert!(bash.contains(
+            "        --format) COMPREPLY=($(compgen -W \"text json\" -- \"$cur\")); return ;;\n"
+        ));
+        assert!(bash.contains("        completions) COMPREPLY=($(compgen -W \"bash zsh fish --name --help\" -- \"$cur\")) ;;\n"));
+        assert!(bash.ends_with("complete -F _aoc aoc\n"));
+
+        let zsh = generate(Shell::Zsh, "aoc");
+        assert!(zsh.starts_with("#compdef aoc\n"));
+        assert!(zsh.contains("'--format[output format, defaults to text]:text|json:(text json)'"));
+
+        let fish = generate(Shell::Fish, "aoc");
+        assert!(fish.contains(
+            "complete -c aoc -n '__fish_seen_subcommand_from bench' -l baseline -x -d 'compare the results to <name>'\n"
+        ));
+    }
+}
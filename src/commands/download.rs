@@ -0,0 +1,33 @@
+use super::{aoc_cli, Day, Error, Globals};
+use std::{env::temp_dir, fs, path::Path};
+
+fn remove_file(path: &Path) {
+    #[allow(unused_must_use)]
+    {
+        fs::remove_file(path);
+    }
+}
+
+pub fn run(day: Day, globals: &Globals) -> Result<(), Error> {
+    // acquire a temp file path to write aoc-cli output to.
+    // aoc-cli expects this file not to be present - delete just in case.
+    let tmp_file_path = temp_dir().join("aoc_input_tmp");
+    remove_file(&tmp_file_path);
+
+    let input_path = format!("src/inputs/{}.txt", day);
+    let args = [
+        "--input-file".into(),
+        tmp_file_path.to_string_lossy().to_string(),
+        "download".into(),
+    ];
+    let result = aoc_cli::run("Downloading input", day, globals.year, &args).and_then(|_| {
+        fs::copy(&tmp_file_path, &input_path)
+            .map_err(|e| Error::Io("could not copy downloaded input to input file".into(), e))
+    });
+    remove_file(&tmp_file_path);
+    result?;
+
+    println!("---");
+    println!("🎄 Successfully wrote input to \"{}\".", &input_path);
+    Ok(())
+}
@@ -0,0 +1,373 @@
+/*
+ * This file contains template code.
+ * There is no need to edit this file unless you want to change template functionality.
+ *
+ * Subcommands of the `advent_of_code` binary. `COMMANDS` describes all of them and is what
+ * both `--help` and the shell completions are generated from.
+ */
+use std::{
+    fmt::{self, Display},
+    io,
+    process::{Command as Process, ExitStatus},
+    str::FromStr,
+};
+
+pub mod all;
+mod aoc_cli;
+pub mod bench;
+pub mod completions;
+pub mod download;
+pub mod scaffold;
+pub mod solve;
+pub mod submit;
+pub mod test;
+
+pub const BIN_NAME: &str = "advent_of_code";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Day(u8);
+
+impl Day {
+    pub fn new(day: u8) -> Option<Self> {
+        (1..=25).contains(&day).then_some(Self(day))
+    }
+
+    pub fn number(self) -> u8 {
+        self.0
+    }
+}
+
+impl FromStr for Day {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse()
+            .ok()
+            .and_then(Self::new)
+            .ok_or_else(|| format!("`{}` is not a day from 1 to 25", s))
+    }
+}
+
+/// Days are padded to two digits, like the names of their files.
+impl Display for Day {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            _ => Err(format!("unknown format `{}`, expected `text` or `json`", s)),
+        }
+    }
+}
+
+/// Flags that are accepted before or after any command, by the commands they apply to.
+#[derive(Debug, Default)]
+pub struct Globals {
+    pub year: Option<u16>,
+    pub release: bool,
+    pub format: Option<Format>,
+}
+
+impl Globals {
+    fn given(&self) -> impl Iterator<Item = &'static str> {
+        [
+            self.year.map(|_| "--year"),
+            self.release.then_some("--release"),
+            self.format.map(|_| "--format"),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    pub fn format(&self) -> Format {
+        self.format.unwrap_or_default()
+    }
+}
+
+pub struct Flag {
+    pub name: &'static str,
+    pub value: Option<&'static str>,
+    pub about: &'static str,
+}
+
+impl Flag {
+    const fn new(name: &'static str, value: Option<&'static str>, about: &'static str) -> Self {
+        Self { name, value, about }
+    }
+
+    fn usage(&self) -> String {
+        match self.value {
+            Some(value) => format!("{} {}", self.name, value),
+            None => self.name.to_string(),
+        }
+    }
+}
+
+pub const GLOBAL_FLAGS: [Flag; 3] = [
+    Flag::new(
+        "--year",
+        Some("<year>"),
+        "puzzle year, defaults to the current one",
+    ),
+    Flag::new("--release", None, "build solutions with optimizations"),
+    Flag::new(
+        "--format",
+        Some("<text|json>"),
+        "output format, defaults to text",
+    ),
+];
+
+const HELP: Flag = Flag::new("-h, --help", None, "print this help");
+
+pub struct Command {
+    pub name: &'static str,
+    /// Usage of the positional arguments.
+    pub args: &'static str,
+    pub about: &'static str,
+    pub flags: &'static [Flag],
+    /// Which of `GLOBAL_FLAGS` the command uses.
+    pub globals: &'static [&'static str],
+}
+
+pub const COMMANDS: [Command; 8] = [
+    Command {
+        name: "scaffold",
+        args: "<day>",
+        about: "Create the solution, input and example files for a day",
+        flags: &[],
+        globals: &[],
+    },
+    Command {
+        name: "download",
+        args: "<day>",
+        about: "Download the input for a day with aoc-cli",
+        flags: &[],
+        globals: &["--year"],
+    },
+    Command {
+        name: "solve",
+        args: "<day> [-- <args>...]",
+        about: "Run the solution for a day, passing <args> on to it",
+        flags: &[],
+        globals: &["--release", "--format"],
+    },
+    Command {
+        name: "all",
+        args: "",
+        about: "Run the solutions for all days and total their timings",
+        flags: &[],
+        globals: &["--release", "--format"],
+    },
+    Command {
+        name: "test",
+        args: "[<day>] [-- <filter>...]",
+        about: "Test the solutions against the examples, or only the solution for a day",
+        flags: &[],
+        globals: &["--release"],
+    },
+    Command {
+        name: "bench",
+        args: "[<day>]",
+        about: "Benchmark the solutions, or only the solution for a day",
+        flags: &[
+            Flag::new(
+                "--save-baseline",
+                Some("<name>"),
+                "save the results as <name>",
+            ),
+            Flag::new(
+                "--baseline",
+                Some("<name>"),
+                "compare the results to <name>",
+            ),
+        ],
+        globals: &[],
+    },
+    Command {
+        name: "submit",
+        args: "<day> <part> [<answer>]",
+        about: "Submit an answer with aoc-cli, running the solution if none is given",
+        flags: &[],
+        globals: &["--year", "--release"],
+    },
+    Command {
+        name: "completions",
+        args: "<bash|zsh|fish>",
+        about: "Print a shell completion script",
+        flags: &[Flag::new(
+            "--name",
+            Some("<name>"),
+            "command to complete, defaults to advent_of_code",
+        )],
+        globals: &[],
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+impl Command {
+    pub fn global_flags(&self) -> impl Iterator<Item = &'static Flag> + '_ {
+        GLOBAL_FLAGS
+            .iter()
+            .filter(|flag| self.globals.contains(&flag.name))
+    }
+
+    /// Fails if a global flag was given that this command has no use for.
+    pub fn check(&self, globals: &Globals) -> Result<(), Error> {
+        match globals.given().find(|flag| !self.globals.contains(flag)) {
+            Some(flag) => Err(Error::Usage(format!(
+                "`{}` does not take {}",
+                self.name, flag
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    pub fn help(&self) -> String {
+        let mut out = format!("{}\n\nusage: {} {}", self.about, BIN_NAME, self.name);
+        for flag in self.flags.iter().chain(self.global_flags()) {
+            out.push_str(&format!(" [{}]", flag.usage()));
+        }
+        if !self.args.is_empty() {
+            out.push_str(&format!(" {}", self.args));
+        }
+        out.push_str("\n\nflags:\n");
+        for flag in self.flags.iter().chain(self.global_flags()).chain([&HELP]) {
+            out.push_str(&format!("  {:<26}{}\n", flag.usage(), flag.about));
+        }
+        out
+    }
+}
+
+pub fn usage() -> String {
+    let mut out = format!(
+        "usage: {} [--year <year>] [--release] [--format <text|json>] <command> [<args>]\n\n",
+        BIN_NAME
+    );
+    out.push_str("commands:\n");
+    for command in &COMMANDS {
+        out.push_str(&format!("  {:<14}{}\n", command.name, command.about));
+    }
+    out.push_str("\nflags:\n");
+    for flag in &GLOBAL_FLAGS {
+        out.push_str(&format!("  {:<26}{}\n", flag.usage(), flag.about));
+    }
+    out.push_str(&format!(
+        "\nRun `{} <command> --help` for the arguments and flags of a command.\n",
+        BIN_NAME
+    ));
+    out
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Args(pico_args::Error),
+    Usage(String),
+    Io(String, io::Error),
+    Failed(String),
+    /// A child process failed and has already told the user why.
+    Exit(i32),
+}
+
+impl From<pico_args::Error> for Error {
+    fn from(e: pico_args::Error) -> Self {
+        Error::Args(e)
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Args(e) => write!(f, "{}", e),
+            Error::Usage(message) | Error::Failed(message) => write!(f, "{}", message),
+            Error::Io(context, e) => write!(f, "{}: {}", context, e),
+            Error::Exit(code) => write!(f, "exited with status {}", code),
+        }
+    }
+}
+
+/// `cargo <subcommand>`, optimized if `--release` was given.
+fn cargo(subcommand: &str, release: bool) -> Process {
+    let mut cmd = Process::new("cargo");
+    cmd.arg(subcommand);
+    if release {
+        cmd.arg("--release");
+    }
+    cmd
+}
+
+/// Runs `cmd` with the terminal attached.
+fn run(mut cmd: Process) -> Result<(), Error> {
+    let program = cmd.get_program().to_string_lossy().to_string();
+    let status = cmd
+        .status()
+        .map_err(|e| Error::Io(format!("could not run `{}`", program), e))?;
+    check_status(status)
+}
+
+fn check_status(status: ExitStatus) -> Result<(), Error> {
+    match status.code() {
+        _ if status.success() => Ok(()),
+        Some(code) => Err(Error::Exit(code)),
+        None => Err(Error::Exit(1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_day() {
+        assert_eq!(
+            "7".parse::<Day>().map(|day| day.to_string()),
+            Ok("07".into())
+        );
+        assert!("0".parse::<Day>().is_err());
+        assert!("26".parse::<Day>().is_err());
+    }
+
+    #[test]
+    fn test_check_globals() {
+        let globals = Globals {
+            release: true,
+            ..Globals::default()
+        };
+        assert!(find("solve").unwrap().check(&globals).is_ok());
+        assert_eq!(
+            find("download")
+                .unwrap()
+                .check(&globals)
+                .unwrap_err()
+                .to_string(),
+            "`download` does not take --release"
+        );
+    }
+
+    #[test]
+    fn test_help() {
+        let help = find("bench").unwrap().help();
+        assert!(help.starts_with(
+            "Benchmark the solutions, or only the solution for a day\n\n\
+             usage: advent_of_code bench [--save-baseline <name>] [--baseline <name>] [<day>]\n"
+        ));
+        assert!(help.contains("\n  -h, --help"));
+        assert!(usage().contains("\n  submit        Submit an answer"));
+    }
+}
@@ -0,0 +1,77 @@
+use super::{Day, Error};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, Write},
+};
+
+const MODULE_TEMPLATE: &str = r###"pub fn part_one(input: &str) -> Option<u32> {
+    None
+}
+
+pub fn part_two(input: &str) -> Option<u32> {
+    None
+}
+
+fn main() {
+    let input = &advent_of_code::read_file("inputs", DAY);
+    advent_of_code::solve!(1, part_one, input);
+    advent_of_code::solve!(2, part_two, input);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_one() {
+        let input = advent_of_code::read_file("examples", DAY);
+        assert_eq!(part_one(&input), None);
+    }
+
+    #[test]
+    fn test_part_two() {
+        let input = advent_of_code::read_file("examples", DAY);
+        assert_eq!(part_two(&input), None);
+    }
+}
+"###;
+
+fn safe_create_file(path: &str) -> Result<File, io::Error> {
+    OpenOptions::new().write(true).create_new(true).open(path)
+}
+
+/// Creates the file if it is missing. An existing file, e.g. an input that was downloaded
+/// before scaffolding, is kept as it is.
+fn create_file(path: &str) -> Result<File, io::Error> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(path)
+}
+
+pub fn run(day: Day) -> Result<(), Error> {
+    let input_path = format!("src/inputs/{}.txt", day);
+    let example_path = format!("src/examples/{}.txt", day);
+    let module_path = format!("src/bin/{}.rs", day);
+
+    let mut file = safe_create_file(&module_path)
+        .map_err(|e| Error::Io("Failed to create module file".into(), e))?;
+    file.write_all(
+        MODULE_TEMPLATE
+            .replace("DAY", &day.number().to_string())
+            .as_bytes(),
+    )
+    .map_err(|e| Error::Io("Failed to write module contents".into(), e))?;
+    println!("Created module file \"{}\"", &module_path);
+
+    create_file(&input_path).map_err(|e| Error::Io("Failed to create input file".into(), e))?;
+    println!("Created empty input file \"{}\"", &input_path);
+
+    create_file(&example_path).map_err(|e| Error::Io("Failed to create example file".into(), e))?;
+    println!("Created empty example file \"{}\"", &example_path);
+
+    println!("---");
+    println!("🎄 Type `cargo solve {}` to run your solution.", day);
+    Ok(())
+}
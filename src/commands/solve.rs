@@ -0,0 +1,143 @@
+use super::{cargo, check_status, Day, Error, Format, Globals};
+use serde::Serialize;
+use std::{
+    ffi::OsString,
+    process::{Command, Stdio},
+};
+
+/// Answer to one part as printed by `solve!`, with its timing in milliseconds.
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Part {
+    pub part: u8,
+    pub answer: Option<String>,
+    pub elapsed_ms: Option<f64>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Report {
+    pub day: u8,
+    pub parts: Vec<Part>,
+}
+
+fn cargo_run(day: Day, release: bool, args: &[OsString]) -> Command {
+    let mut cmd = cargo("run", release);
+    cmd.args(["--bin", &day.to_string()]);
+    if !args.is_empty() {
+        cmd.arg("--").args(args);
+    }
+    cmd
+}
+
+/// Runs the solution for `day` and returns what it printed. Build errors still go to the
+/// terminal.
+pub fn capture(day: Day, release: bool, args: &[OsString]) -> Result<String, Error> {
+    let output = cargo_run(day, release, args)
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| Error::Io("could not run `cargo`".into(), e))?;
+    check_status(output.status)?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+pub fn report(day: Day, release: bool, args: &[OsString]) -> Result<Report, Error> {
+    let output = capture(day, release, args)?;
+    Ok(report_from(day, &output))
+}
+
+pub fn report_from(day: Day, output: &str) -> Report {
+    Report {
+        day: day.number(),
+        parts: parse_parts(output),
+    }
+}
+
+fn strip_ansi(line: &str) -> String {
+    let mut out = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // skip a sequence like `\x1b[3m`.
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// Reads the answers from the output of `solve!`: a `🎄 Part N 🎄` header, then possibly
+/// whatever else the solution prints, then `<answer> (elapsed: <time>)` or `not solved.`.
+fn parse_parts(output: &str) -> Vec<Part> {
+    let mut parts = vec![];
+    let mut current = None;
+    for line in output.lines() {
+        let plain = strip_ansi(line);
+        let header = plain
+            .strip_prefix("🎄 Part ")
+            .and_then(|rest| rest.strip_suffix(" 🎄"));
+        if let Some(part) = header {
+            current = part.parse().ok();
+            continue;
+        }
+        let Some(part) = current else {
+            continue;
+        };
+        if plain == "not solved." {
+            parts.push(Part {
+                part,
+                answer: None,
+                elapsed_ms: None,
+            });
+            current = None;
+        } else if let Some((answer, _)) = plain.rsplit_once(" (elapsed: ") {
+            parts.push(Part {
+                part,
+                answer: Some(answer.to_string()),
+                elapsed_ms: Some(advent_of_code::parse_exec_time(&plain)),
+            });
+            current = None;
+        }
+    }
+    parts
+}
+
+pub fn run(day: Day, globals: &Globals, args: &[OsString]) -> Result<(), Error> {
+    match globals.format() {
+        // keep the terminal attached, for solutions that read from it.
+        Format::Text => super::run(cargo_run(day, globals.release, args)),
+        Format::Json => {
+            let report = report(day, globals.release, args)?;
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_parts() {
+        let output = "🎄 \x1b[1mPart 1\x1b[0m 🎄\n\
+                      24000 \x1b[3m(elapsed: 1.50ms)\x1b[0m\n\
+                      🎄 \x1b[1mPart 2\x1b[0m 🎄\n\
+                      ###..\n\
+                      not solved.\n";
+        assert_eq!(
+            parse_parts(output),
+            [
+                Part {
+                    part: 1,
+                    answer: Some("24000".into()),
+                    elapsed_ms: Some(1.5),
+                },
+                Part {
+                    part: 2,
+                    answer: None,
+                    elapsed_ms: None,
+                },
+            ]
+        );
+    }
+}
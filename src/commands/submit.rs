@@ -0,0 +1,21 @@
+use super::{aoc_cli, solve, Day, Error, Globals};
+
+pub fn run(day: Day, part: u8, answer: Option<String>, globals: &Globals) -> Result<(), Error> {
+    if !(1..=2).contains(&part) {
+        return Err(Error::Usage(format!(
+            "`{}` is not a part, expected 1 or 2",
+            part
+        )));
+    }
+    let answer = match answer {
+        Some(answer) => answer,
+        None => solve::report(day, globals.release, &[])?
+            .parts
+            .into_iter()
+            .find(|p| p.part == part)
+            .and_then(|p| p.answer)
+            .ok_or_else(|| Error::Failed(format!("day {} part {} is not solved", day, part)))?,
+    };
+    let args = ["submit".into(), part.to_string(), answer];
+    aoc_cli::run("Submitting answer", day, globals.year, &args)
+}
@@ -0,0 +1,15 @@
+use super::{cargo, Day, Error, Globals};
+use std::ffi::OsString;
+
+/// `cargo test`, for the solution of `day` only if given, with `filter` passed on to the
+/// test harness.
+pub fn run(day: Option<Day>, globals: &Globals, filter: &[OsString]) -> Result<(), Error> {
+    let mut cmd = cargo("test", globals.release);
+    if let Some(day) = day {
+        cmd.args(["--bin", &day.to_string()]);
+    }
+    if !filter.is_empty() {
+        cmd.arg("--").args(filter);
+    }
+    super::run(cmd)
+}
@@ -0,0 +1,293 @@
+/*
+ * Seeded generators for random but valid puzzle inputs, for benchmarking at scale and
+ * fuzzing. The same day, size and seed always give the same input.
+ */
+use crate::helpers::Rng;
+use std::fmt::Write;
+
+/// The days that have a generator, with what `size` means for each of them.
+pub const DAYS: [(u8, &str); 10] = [
+    (1, "elves"),
+    (2, "rounds"),
+    (3, "rucksacks, rounded up to whole groups of three"),
+    (4, "section assignment pairs"),
+    (5, "moves on nine stacks"),
+    (6, "characters before a guaranteed start-of-message marker"),
+    (7, "directories"),
+    (8, "rows and columns of the grid"),
+    (9, "moves"),
+    (10, "instructions"),
+];
+
+/// Input for `day` of roughly `size` elements, or `None` if there is no generator for it.
+pub fn generate(day: u8, size: usize, seed: u64) -> Option<String> {
+    let rng = &mut Rng::new(seed);
+    let input = match day {
+        1 => calories(rng, size),
+        2 => strategy_guide(rng, size),
+        3 => rucksacks(rng, size),
+        4 => section_pairs(rng, size),
+        5 => crates(rng, size),
+        6 => signal(rng, size),
+        7 => transcript(rng, size),
+        8 => height_grid(rng, size),
+        9 => rope_moves(rng, size),
+        10 => program(rng, size),
+        _ => return None,
+    };
+    Some(input)
+}
+
+const LOWERCASE: &str = "abcdefghijklmnopqrstuvwxyz";
+const NAMES: [&str; 8] = ["bcd", "dnz", "hqr", "lgm", "pjw", "qtv", "rbs", "zfn"];
+const EXTENSIONS: [&str; 4] = ["", ".txt", ".dat", ".log"];
+
+fn calories(rng: &mut Rng, elves: usize) -> String {
+    (0..elves)
+        .map(|_| {
+            let items = rng.between(1, 15);
+            (0..items)
+                .map(|_| format!("{}\n", rng.between(1000, 69999)))
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn strategy_guide(rng: &mut Rng, rounds: usize) -> String {
+    (0..rounds)
+        .map(|_| {
+            format!(
+                "{} {}\n",
+                rng.pick(&['A', 'B', 'C']),
+                rng.pick(&['X', 'Y', 'Z'])
+            )
+        })
+        .collect()
+}
+
+/// Every rucksack has exactly one item in both compartments and every group of three
+/// exactly one item in common, like the real input.
+fn rucksacks(rng: &mut Rng, rucksacks: usize) -> String {
+    let mut items = LOWERCASE
+        .chars()
+        .chain(LOWERCASE.to_uppercase().chars())
+        .collect::<Vec<_>>();
+    let mut out = String::new();
+    for _ in 0..rucksacks.div_ceil(3) {
+        rng.shuffle(&mut items);
+        let badge = items[0];
+        // items private to each elf of the group, so only the badge is shared by all three.
+        for private in items[1..].chunks(17) {
+            let shared = if rng.below(4) == 0 { badge } else { private[0] };
+            let others = private.iter().filter(|&&c| c != shared).copied();
+            let others = others.collect::<Vec<_>>();
+            let (left_items, right_items) = others.split_at(others.len() / 2);
+            let (mut left, mut right) = (vec![shared], vec![shared]);
+            if shared != badge {
+                match rng.below(2) {
+                    0 => left.push(badge),
+                    _ => right.push(badge),
+                }
+            }
+            let len = rng.between(3, 16) as usize;
+            for (half, pool) in [(&mut left, left_items), (&mut right, right_items)] {
+                while half.len() < len {
+                    half.push(*rng.pick(pool));
+                }
+                rng.shuffle(half);
+            }
+            out.extend(left.into_iter().chain(right));
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn section_pairs(rng: &mut Rng, pairs: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..pairs {
+        let (a, c) = (rng.between(1, 99), rng.between(1, 99));
+        let (b, d) = (rng.between(a, 99), rng.between(c, 99));
+        writeln!(out, "{}-{},{}-{}", a, b, c, d).unwrap();
+    }
+    out
+}
+
+/// A drawing of nine stacks followed by moves that never take more crates than a stack has.
+fn crates(rng: &mut Rng, moves: usize) -> String {
+    let mut stacks = (0..9)
+        .map(|_| {
+            let height = rng.between(0, 8);
+            (0..height)
+                .map(|_| (b'A' + rng.below(26) as u8) as char)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if stacks.iter().all(Vec::is_empty) {
+        stacks[0].push('A');
+    }
+
+    let mut out = String::new();
+    let height = stacks.iter().map(Vec::len).max().unwrap_or(0);
+    for row in (0..height).rev() {
+        let cells = stacks.iter().map(|stack| match stack.get(row) {
+            Some(c) => format!("[{}]", c),
+            None => "   ".to_string(),
+        });
+        writeln!(out, "{}", cells.collect::<Vec<_>>().join(" ")).unwrap();
+    }
+    let labels = (1..=stacks.len()).map(|i| format!(" {} ", i));
+    writeln!(out, "{}\n", labels.collect::<Vec<_>>().join(" ")).unwrap();
+
+    // only the heights matter for which moves are legal.
+    let mut heights = stacks.iter().map(Vec::len).collect::<Vec<_>>();
+    for _ in 0..moves {
+        let from = loop {
+            let from = rng.below(9) as usize;
+            if heights[from] > 0 {
+                break from;
+            }
+        };
+        let to = (from + 1 + rng.below(8) as usize) % 9;
+        let num = rng.between(1, heights[from] as i64) as usize;
+        heights[from] -= num;
+        heights[to] += num;
+        writeln!(out, "move {} from {} to {}", num, from + 1, to + 1).unwrap();
+    }
+    out
+}
+
+/// Random letters, ending in fourteen different ones so both markers exist.
+fn signal(rng: &mut Rng, len: usize) -> String {
+    let letters = LOWERCASE.as_bytes();
+    let mut out = (0..len)
+        .map(|_| *rng.pick(letters) as char)
+        .collect::<String>();
+    let mut tail = letters.to_vec();
+    rng.shuffle(&mut tail);
+    out.extend(tail[..14].iter().map(|&b| b as char));
+    out.push('\n');
+    out
+}
+
+/// A shell session that lists every directory exactly once, walking the tree depth first.
+/// Files get smaller as directories get more numerous, so that even if every directory had
+/// the most and largest files, `/` would stay below a quarter of `u32::MAX`.
+fn transcript(rng: &mut Rng, dirs: usize) -> String {
+    const MAX_FILES: usize = 4;
+    // directory 0 is `/`, every other one hangs off a random earlier one.
+    let mut children = vec![vec![]; dirs.max(1)];
+    for dir in 1..children.len() {
+        let parent = rng.below(dir as u64) as usize;
+        children[parent].push(dir);
+    }
+    let max_size = (u32::MAX as usize / 4 / (MAX_FILES * children.len())).clamp(1, 300_000);
+    let sizes = ((max_size / 300).max(1) as i64, max_size as i64);
+
+    fn walk(
+        rng: &mut Rng,
+        children: &[Vec<usize>],
+        sizes: (i64, i64),
+        dir: usize,
+        out: &mut String,
+    ) {
+        out.push_str("$ ls\n");
+        let mut entries = children[dir]
+            .iter()
+            .map(|child| format!("dir {}{}", rng.pick(&NAMES), child))
+            .collect::<Vec<_>>();
+        let subdirs = entries
+            .iter()
+            .map(|entry| entry["dir ".len()..].to_string())
+            .collect::<Vec<_>>();
+        for file in 0..rng.between(0, MAX_FILES as i64) {
+            let name = format!("{}_{}{}", rng.pick(&NAMES), file, rng.pick(&EXTENSIONS));
+            entries.push(format!("{} {}", rng.between(sizes.0, sizes.1), name));
+        }
+        rng.shuffle(&mut entries);
+        for entry in entries {
+            writeln!(out, "{}", entry).unwrap();
+        }
+        for (&child, name) in children[dir].iter().zip(subdirs) {
+            writeln!(out, "$ cd {}", name).unwrap();
+            walk(rng, children, sizes, child, out);
+            out.push_str("$ cd ..\n");
+        }
+    }
+
+    let mut out = String::from("$ cd /\n");
+    walk(rng, &children, sizes, 0, &mut out);
+    out
+}
+
+fn height_grid(rng: &mut Rng, size: usize) -> String {
+    let mut out = String::new();
+    for _ in 0..size {
+        out.extend((0..size).map(|_| (b'0' + rng.below(10) as u8) as char));
+        out.push('\n');
+    }
+    out
+}
+
+fn rope_moves(rng: &mut Rng, moves: usize) -> String {
+    (0..moves)
+        .map(|_| {
+            format!(
+                "{} {}\n",
+                rng.pick(&['R', 'L', 'U', 'D']),
+                rng.between(1, 19)
+            )
+        })
+        .collect()
+}
+
+/// Keeps the register on the screen, so the CRT draws something.
+fn program(rng: &mut Rng, instructions: usize) -> String {
+    let mut x = 1;
+    let mut out = String::new();
+    for _ in 0..instructions {
+        if rng.below(3) == 0 {
+            out.push_str("noop\n");
+            continue;
+        }
+        let mut v = rng.between(-15, 15);
+        if !(0..40).contains(&(x + v)) {
+            v = -v;
+        }
+        x += v;
+        writeln!(out, "addx {}", v).unwrap();
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate() {
+        for (day, _) in DAYS {
+            let input = generate(day, 20, 7).unwrap();
+            assert_eq!(Some(&input), generate(day, 20, 7).as_ref());
+            assert_ne!(Some(&input), generate(day, 20, 8).as_ref());
+            assert!(input.ends_with('\n'), "day {}", day);
+        }
+        assert_eq!(generate(25, 20, 7), None);
+        assert_eq!(generate(8, 3, 1).unwrap().lines().count(), 3);
+        assert_eq!(generate(3, 4, 1).unwrap().lines().count(), 6);
+    }
+
+    #[test]
+    fn test_transcript_fits() {
+        // well past the sizes the README and the benchmarks use.
+        for dirs in [1, 1000, 10_000, 100_000] {
+            let total = generate(7, dirs, 1)
+                .unwrap()
+                .lines()
+                .filter_map(|line| line.split_once(' ')?.0.parse::<u64>().ok())
+                .sum::<u64>();
+            assert!(total <= u32::MAX as u64 / 4, "{} for {} dirs", total, dirs);
+        }
+    }
+}
@@ -2,3 +2,98 @@
  * Use this file if you want to extract helpers from your solutions.
  * Example import from this file: `use advent_of_code::helpers::example_fn;`.
  */
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Small seeded xorshift generator, so random inputs in tests are reproducible without
+/// pulling in a dependency. Not suitable for anything but test data.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift gets stuck on zero, so scramble the seed first.
+        Self(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Uniform-ish number in `0..n`.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "empty range");
+        self.next_u64() % n
+    }
+
+    /// Number in `lo..=hi`.
+    pub fn between(&mut self, lo: i64, hi: i64) -> i64 {
+        assert!(lo <= hi, "empty range");
+        lo + self.below((hi - lo) as u64 + 1) as i64
+    }
+
+    pub fn pick<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.below(items.len() as u64) as usize]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.below(i as u64 + 1) as usize);
+        }
+    }
+}
+
+/// The `k` largest items, largest first, keeping at most `k` of them around at any time.
+pub fn top_k<T: Ord>(items: impl IntoIterator<Item = T>, k: usize) -> Vec<T> {
+    let mut heap = BinaryHeap::with_capacity(k + 1);
+    for item in items {
+        heap.push(Reverse(item));
+        if heap.len() > k {
+            heap.pop();
+        }
+    }
+    // ascending order of `Reverse` is descending order of the items.
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse(item)| item)
+        .collect()
+}
+
+/// Fails on the first argument that was not parsed, which is most likely a misspelled flag.
+pub fn finish_args(args: pico_args::Arguments) -> Result<(), pico_args::Error> {
+    match args.finish().first() {
+        Some(arg) => Err(pico_args::Error::Utf8ArgumentParsingFailed {
+            value: arg.to_string_lossy().into_owned(),
+            cause: "unexpected argument".into(),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_top_k() {
+        let items = [5, 1, 9, 3, 9, 7];
+        assert_eq!(top_k(items, 3), [9, 9, 7]);
+        assert_eq!(top_k(items, 10), [9, 9, 7, 5, 3, 1]);
+        assert_eq!(top_k(items, 0), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_finish_args() {
+        let args =
+            |args: &[&str]| pico_args::Arguments::from_vec(args.iter().map(Into::into).collect());
+        assert!(finish_args(args(&[])).is_ok());
+        let mut given = args(&["--all", "--alll"]);
+        assert!(given.contains("--all"));
+        assert_eq!(
+            finish_args(given).unwrap_err().to_string(),
+            "failed to parse '--alll': unexpected argument"
+        );
+    }
+}
@@ -6,6 +6,8 @@
 use std::env;
 use std::fs;
 
+pub mod check;
+pub mod gen;
 pub mod helpers;
 
 pub const ANSI_ITALIC: &str = "\x1b[3m";
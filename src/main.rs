@@ -2,47 +2,137 @@
  * This file contains template code.
  * There is no need to edit this file unless you want to change template functionality.
  */
-use advent_of_code::{ANSI_BOLD, ANSI_ITALIC, ANSI_RESET};
-use std::process::Command;
+mod commands;
 
-fn main() {
-    let total: f64 = (1..=25)
-        .map(|day| {
-            let day = format!("{:02}", day);
+use commands::{bench::Baseline, completions::Shell, Day, Error, Globals, BIN_NAME};
+use pico_args::Arguments;
+use std::{env, ffi::OsString, process};
 
-            let mut args = vec!["run", "--bin", &day];
-            if cfg!(not(debug_assertions)) {
-                args.push("--release");
-            }
+/// Splits off the arguments after `--`, which pico-args would otherwise parse as flags.
+fn split_passthrough(mut args: Vec<OsString>) -> (Vec<OsString>, Vec<OsString>) {
+    match args.iter().position(|arg| arg == "--") {
+        Some(i) => {
+            let passthrough = args.split_off(i + 1);
+            args.pop();
+            (args, passthrough)
+        }
+        None => (args, vec![]),
+    }
+}
 
-            let cmd = Command::new("cargo").args(&args).output().unwrap();
+fn finish(args: Arguments) -> Result<(), Error> {
+    match args.finish().first() {
+        Some(arg) => Err(Error::Usage(format!(
+            "unexpected argument `{}`",
+            arg.to_string_lossy()
+        ))),
+        None => Ok(()),
+    }
+}
 
-            println!("----------");
-            println!("{}| Day {} |{}", ANSI_BOLD, day, ANSI_RESET);
-            println!("----------");
+fn run(args: Vec<OsString>) -> Result<(), Error> {
+    let (args, passthrough) = split_passthrough(args);
+    let mut args = Arguments::from_vec(args);
+    let help = args.contains(["-h", "--help"]);
+    let globals = Globals {
+        year: args.opt_value_from_str(["-y", "--year"])?,
+        release: args.contains("--release"),
+        format: args.opt_value_from_str("--format")?,
+    };
 
-            let output = String::from_utf8(cmd.stdout).unwrap();
-            let is_empty = output.is_empty();
+    let Some(name) = args.subcommand()? else {
+        if help {
+            print!("{}", commands::usage());
+            return Ok(());
+        }
+        return Err(Error::Usage("no command given".into()));
+    };
+    let command =
+        commands::find(&name).ok_or_else(|| Error::Usage(format!("unknown command `{}`", name)))?;
+    if help {
+        print!("{}", command.help());
+        return Ok(());
+    }
+    command.check(&globals)?;
+    if !passthrough.is_empty() && !command.args.contains("--") {
+        return Err(Error::Usage(format!(
+            "`{}` does not take arguments after --",
+            name
+        )));
+    }
 
-            println!(
-                "{}",
-                if is_empty {
-                    "Not solved."
-                } else {
-                    output.trim()
+    match command.name {
+        "scaffold" => {
+            let day = args.free_from_str()?;
+            finish(args)?;
+            commands::scaffold::run(day)
+        }
+        "download" => {
+            let day = args.free_from_str()?;
+            finish(args)?;
+            commands::download::run(day, &globals)
+        }
+        "solve" => {
+            let day = args.free_from_str()?;
+            finish(args)?;
+            commands::solve::run(day, &globals, &passthrough)
+        }
+        "all" => {
+            finish(args)?;
+            commands::all::run(&globals)
+        }
+        "test" => {
+            let day = args.opt_free_from_str::<Day>()?;
+            finish(args)?;
+            commands::test::run(day, &globals, &passthrough)
+        }
+        "bench" => {
+            let save = args.opt_value_from_str("--save-baseline")?;
+            let compare = args.opt_value_from_str("--baseline")?;
+            let day = args.opt_free_from_str::<Day>()?;
+            finish(args)?;
+            let baseline = match (save, compare) {
+                (Some(_), Some(_)) => {
+                    return Err(Error::Usage(
+                        "--save-baseline and --baseline cannot be used together".into(),
+                    ))
                 }
-            );
-
-            if is_empty {
-                0_f64
-            } else {
-                advent_of_code::parse_exec_time(&output)
-            }
-        })
-        .sum();
+                (Some(name), None) => Some(Baseline::Save(name)),
+                (None, Some(name)) => Some(Baseline::Compare(name)),
+                (None, None) => None,
+            };
+            commands::bench::run(day, baseline)
+        }
+        "submit" => {
+            let day = args.free_from_str()?;
+            let part = args.free_from_str()?;
+            let answer = args.opt_free_from_str()?;
+            finish(args)?;
+            commands::submit::run(day, part, answer, &globals)
+        }
+        "completions" => {
+            let name: Option<String> = args.opt_value_from_str("--name")?;
+            let shell = args.free_from_str::<Shell>()?;
+            finish(args)?;
+            commands::completions::run(shell, name.as_deref().unwrap_or(BIN_NAME))
+        }
+        _ => unreachable!("`{}` is in COMMANDS but not handled", command.name),
+    }
+}
 
-    println!(
-        "{}Total:{} {}{:.2}ms{}",
-        ANSI_BOLD, ANSI_RESET, ANSI_ITALIC, total, ANSI_RESET
-    );
+fn main() {
+    match run(env::args_os().skip(1).collect()) {
+        Ok(()) => {}
+        // the child process already reported what went wrong.
+        Err(Error::Exit(code)) => process::exit(code),
+        Err(e @ (Error::Args(_) | Error::Usage(_))) => {
+            eprintln!("error: {}", e);
+            eprintln!("Run `{} --help` for usage.", BIN_NAME);
+            process::exit(2);
+        }
+        Err(e) => {
+            eprintln!("error: {}", e);
+            process::exit(1);
+        }
+    }
 }